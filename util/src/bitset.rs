@@ -0,0 +1,344 @@
+use std::fmt::{Debug, Formatter};
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+use itertools::Itertools;
+
+/// A set of small integers below `64 * WORDS`, stored inline so it's cheap to copy and hash as
+/// part of a search state.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct BitSet<const WORDS: usize> {
+    words: [u64; WORDS],
+}
+
+pub type BitSet64 = BitSet<1>;
+pub type BitSet128 = BitSet<2>;
+
+impl<const WORDS: usize> Default for BitSet<WORDS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const WORDS: usize> BitSet<WORDS> {
+    pub const CAPACITY: usize = 64 * WORDS;
+
+    pub const fn new() -> Self {
+        Self { words: [0; WORDS] }
+    }
+
+    /// The set {0, 1, ..., n - 1}.
+    pub fn first_n(n: usize) -> Self {
+        assert!(n <= Self::CAPACITY, "{} doesn't fit in a BitSet", n);
+        let mut result = Self::new();
+        for (idx, word) in result.words.iter_mut().enumerate() {
+            let bits = n.saturating_sub(idx * 64).min(64);
+            *word = if bits == 64 {
+                u64::MAX
+            } else {
+                (1 << bits) - 1
+            };
+        }
+        result
+    }
+
+    /// Lowest bits first.
+    pub const fn from_words(words: [u64; WORDS]) -> Self {
+        Self { words }
+    }
+
+    pub fn words(&self) -> [u64; WORDS] {
+        self.words
+    }
+
+    /// Returns whether the value was newly added.
+    pub fn insert(&mut self, value: usize) -> bool {
+        let (word, mask) = Self::locate(value);
+        let was_absent = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+        was_absent
+    }
+
+    /// Returns whether the value was present.
+    pub fn remove(&mut self, value: usize) -> bool {
+        let (word, mask) = Self::locate(value);
+        let was_present = self.words[word] & mask != 0;
+        self.words[word] &= !mask;
+        was_present
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        value < Self::CAPACITY && {
+            let (word, mask) = Self::locate(value);
+            self.words[word] & mask != 0
+        }
+    }
+
+    /// A copy with the value added.
+    pub fn with(mut self, value: usize) -> Self {
+        self.insert(value);
+        self
+    }
+
+    /// A copy with the value removed.
+    pub fn without(mut self, value: usize) -> Self {
+        self.remove(value);
+        self
+    }
+
+    /// The number of values in the set.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// The smallest value in the set.
+    pub fn first(&self) -> Option<usize> {
+        self.words
+            .iter()
+            .enumerate()
+            .find(|(_, w)| **w != 0)
+            .map(|(idx, w)| idx * 64 + w.trailing_zeros() as usize)
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        (*self & !*other).is_empty()
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        (*self & *other).is_empty()
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        *self & !*other
+    }
+
+    /// The values in ascending order.
+    pub fn iter(&self) -> Iter<WORDS> {
+        Iter { remaining: *self }
+    }
+
+    /// Every subset of this set, including itself and the empty set, from largest bitmask to
+    /// smallest. There are 2^len of them.
+    pub fn submasks(&self) -> Submasks<WORDS> {
+        Submasks {
+            mask: *self,
+            next: Some(*self),
+        }
+    }
+
+    /// Every subset with exactly k values.
+    pub fn subsets_of_size(&self, k: usize) -> impl Iterator<Item = Self> {
+        self.iter()
+            .combinations(k)
+            .map(|values| values.into_iter().collect())
+    }
+
+    fn locate(value: usize) -> (usize, u64) {
+        assert!(
+            value < Self::CAPACITY,
+            "{} doesn't fit in a BitSet of capacity {}",
+            value,
+            Self::CAPACITY
+        );
+        (value / 64, 1 << (value % 64))
+    }
+
+    /// self - 1, treating the words as one big little-endian number. Wraps around at zero.
+    fn decremented(&self) -> Self {
+        let mut result = *self;
+        for word in result.words.iter_mut() {
+            let (value, borrow) = word.overflowing_sub(1);
+            *word = value;
+            if !borrow {
+                break;
+            }
+        }
+        result
+    }
+}
+
+impl<const WORDS: usize> Debug for BitSet<WORDS> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<const WORDS: usize> FromIterator<usize> for BitSet<WORDS> {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut result = Self::new();
+        result.extend(iter);
+        result
+    }
+}
+
+impl<const WORDS: usize> Extend<usize> for BitSet<WORDS> {
+    fn extend<T: IntoIterator<Item = usize>>(&mut self, iter: T) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<const WORDS: usize> IntoIterator for BitSet<WORDS> {
+    type Item = usize;
+    type IntoIter = Iter<WORDS>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl From<u64> for BitSet64 {
+    fn from(bits: u64) -> Self {
+        Self::from_words([bits])
+    }
+}
+
+impl From<BitSet64> for u64 {
+    fn from(set: BitSet64) -> Self {
+        set.words[0]
+    }
+}
+
+impl From<u128> for BitSet128 {
+    fn from(bits: u128) -> Self {
+        Self::from_words([bits as u64, (bits >> 64) as u64])
+    }
+}
+
+impl From<BitSet128> for u128 {
+    fn from(set: BitSet128) -> Self {
+        set.words[0] as u128 | (set.words[1] as u128) << 64
+    }
+}
+
+macro_rules! impl_bit_op {
+    ($op:ident, $op_fn:ident, $assign:ident, $assign_fn:ident) => {
+        impl<const WORDS: usize> $op for BitSet<WORDS> {
+            type Output = Self;
+
+            fn $op_fn(mut self, rhs: Self) -> Self {
+                self.$assign_fn(rhs);
+                self
+            }
+        }
+
+        impl<const WORDS: usize> $assign for BitSet<WORDS> {
+            fn $assign_fn(&mut self, rhs: Self) {
+                for (word, other) in self.words.iter_mut().zip(rhs.words) {
+                    word.$assign_fn(other);
+                }
+            }
+        }
+    };
+}
+
+impl_bit_op!(BitOr, bitor, BitOrAssign, bitor_assign);
+impl_bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign);
+impl_bit_op!(BitXor, bitxor, BitXorAssign, bitxor_assign);
+
+impl<const WORDS: usize> Not for BitSet<WORDS> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self {
+            words: self.words.map(|w| !w),
+        }
+    }
+}
+
+pub struct Iter<const WORDS: usize> {
+    remaining: BitSet<WORDS>,
+}
+
+impl<const WORDS: usize> Iterator for Iter<WORDS> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let value = self.remaining.first()?;
+        self.remaining.remove(value);
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining.len();
+        (len, Some(len))
+    }
+}
+
+pub struct Submasks<const WORDS: usize> {
+    mask: BitSet<WORDS>,
+    next: Option<BitSet<WORDS>>,
+}
+
+impl<const WORDS: usize> Iterator for Submasks<WORDS> {
+    type Item = BitSet<WORDS>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        self.next = if current.is_empty() {
+            None
+        } else {
+            Some(current.decremented() & self.mask)
+        };
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use crate::bitset::{BitSet128, BitSet64};
+
+    #[test]
+    fn test_basics() {
+        let mut set = BitSet128::new();
+        assert!(set.insert(3));
+        assert!(!set.insert(3));
+        set.insert(70);
+        set.insert(127);
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.iter().collect_vec(), vec![3, 70, 127]);
+        assert!(set.contains(70) && !set.contains(69) && !set.contains(500));
+        assert_eq!(set.first(), Some(3));
+        assert_eq!(u128::from(set), 1 << 3 | 1 << 70 | 1 << 127);
+        assert!(set.remove(3));
+        assert_eq!(format!("{:?}", set), "{70, 127}");
+
+        let evens: BitSet64 = (0..64).step_by(2).collect();
+        let small = BitSet64::first_n(10);
+        assert_eq!((evens & small).len(), 5);
+        assert_eq!(evens.difference(&small).first(), Some(10));
+        assert!(BitSet64::from(0b10100).is_subset(&evens));
+        assert_eq!(BitSet128::first_n(100).len(), 100);
+        assert_eq!(BitSet64::first_n(64), !BitSet64::new());
+    }
+
+    #[test]
+    fn test_submasks() {
+        let mask = BitSet64::from(0b1101);
+        let submasks = mask.submasks().map(u64::from).collect_vec();
+        assert_eq!(
+            submasks,
+            vec![0b1101, 0b1100, 0b1001, 0b1000, 0b0101, 0b0100, 0b0001, 0]
+        );
+        assert_eq!(BitSet64::new().submasks().count(), 1);
+
+        // Spanning a word boundary
+        let wide: BitSet128 = [1, 63, 64, 100].into_iter().collect();
+        let all = wide.submasks().collect_vec();
+        assert_eq!(all.len(), 16);
+        assert!(all.iter().all(|s| s.is_subset(&wide)));
+        assert!(all.iter().all_unique());
+
+        assert_eq!(wide.subsets_of_size(2).count(), 6);
+    }
+}
@@ -0,0 +1,194 @@
+use std::array;
+
+use crate::point3::{max, min, PointValue};
+
+/// An axis-aligned box in N dimensions. The start is inclusive and the end is exclusive, to match
+/// `BoundingBoxG`.
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub struct HyperBox<PV: PointValue, const N: usize> {
+    pub start: [PV; N],
+    pub end: [PV; N],
+}
+
+impl<PV: PointValue, const N: usize> HyperBox<PV, N> {
+    pub const fn new(start: [PV; N], end: [PV; N]) -> Self {
+        Self { start, end }
+    }
+
+    /// Builds a box from inclusive ranges on each axis, eg from an input like `x=1..3,y=2..5`.
+    pub fn from_inclusive(start: [PV; N], last: [PV; N]) -> Self {
+        Self::new(start, last.map(|v| v + PV::ONE))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..N).any(|d| self.start[d] >= self.end[d])
+    }
+
+    /// Panics if a side length doesn't fit in an i128 or the result overflows.
+    pub fn volume(&self) -> i128 {
+        if self.is_empty() {
+            return 0;
+        }
+        (0..N)
+            .map(|d| num_traits::cast::<PV, i128>(self.end[d] - self.start[d]).unwrap())
+            .fold(1i128, |acc, side| acc.checked_mul(side).unwrap())
+    }
+
+    pub fn contains(&self, point: &[PV; N]) -> bool {
+        (0..N).all(|d| self.start[d] <= point[d] && point[d] < self.end[d])
+    }
+
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let result = Self::new(
+            array::from_fn(|d| max(self.start[d], other.start[d])),
+            array::from_fn(|d| min(self.end[d], other.end[d])),
+        );
+        (!result.is_empty()).then_some(result)
+    }
+
+    /// Returns disjoint boxes that together cover everything in self that isn't in other. At most
+    /// 2N boxes are produced.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let Some(intersection) = self.intersect(other) else {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        };
+        // Peel off a slab on each side of the intersection, one axis at a time, shrinking what's
+        // left until only the intersection itself remains.
+        let mut pieces = Vec::new();
+        let mut remaining = *self;
+        for d in 0..N {
+            if remaining.start[d] < intersection.start[d] {
+                let mut piece = remaining;
+                piece.end[d] = intersection.start[d];
+                pieces.push(piece);
+                remaining.start[d] = intersection.start[d];
+            }
+            if intersection.end[d] < remaining.end[d] {
+                let mut piece = remaining;
+                piece.start[d] = intersection.end[d];
+                pieces.push(piece);
+                remaining.end[d] = intersection.end[d];
+            }
+        }
+        pieces
+    }
+}
+
+/// A set of cells in N-D space, stored as a list of disjoint boxes. Adding and removing boxes
+/// only ever splits the boxes they overlap, so the number of pieces stays manageable even for
+/// large coordinates.
+#[derive(Debug, Clone)]
+pub struct BoxSet<PV: PointValue, const N: usize> {
+    boxes: Vec<HyperBox<PV, N>>,
+}
+
+impl<PV: PointValue, const N: usize> Default for BoxSet<PV, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<PV: PointValue, const N: usize> BoxSet<PV, N> {
+    pub fn new() -> Self {
+        Self { boxes: Vec::new() }
+    }
+
+    pub fn add(&mut self, bound: impl Into<HyperBox<PV, N>>) {
+        let bound = bound.into();
+        if bound.is_empty() {
+            return;
+        }
+        self.remove(bound);
+        self.boxes.push(bound);
+    }
+
+    pub fn remove(&mut self, bound: impl Into<HyperBox<PV, N>>) {
+        let bound = bound.into();
+        self.boxes = self
+            .boxes
+            .iter()
+            .flat_map(|existing| existing.subtract(&bound))
+            .collect();
+    }
+
+    /// Returns only the parts of this set which are also within the bound.
+    pub fn clipped(&self, bound: impl Into<HyperBox<PV, N>>) -> Self {
+        let bound = bound.into();
+        Self {
+            boxes: self
+                .boxes
+                .iter()
+                .filter_map(|existing| existing.intersect(&bound))
+                .collect(),
+        }
+    }
+
+    pub fn volume(&self) -> i128 {
+        self.boxes.iter().map(|b| b.volume()).sum()
+    }
+
+    pub fn contains(&self, point: impl Into<[PV; N]>) -> bool {
+        let point = point.into();
+        self.boxes.iter().any(|b| b.contains(&point))
+    }
+
+    /// Iterates over disjoint boxes which exactly cover the set, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &HyperBox<PV, N>> {
+        self.boxes.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.boxes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::box_set::{BoxSet, HyperBox};
+    use crate::point3::{BoundingBox, BoxSet3, Point3};
+
+    #[test]
+    fn test_subtract_pieces_are_disjoint() {
+        let outer = HyperBox::new([0, 0, 0], [4, 4, 4]);
+        let inner = HyperBox::new([1, 1, 1], [3, 3, 3]);
+        let pieces = outer.subtract(&inner);
+        assert_eq!(pieces.len(), 6);
+        assert_eq!(pieces.iter().map(|p| p.volume()).sum::<i128>(), 64 - 8);
+        for (i, a) in pieces.iter().enumerate() {
+            assert!(a.intersect(&inner).is_none());
+            for b in pieces[i + 1..].iter() {
+                assert!(a.intersect(b).is_none());
+            }
+        }
+        assert_eq!(inner.subtract(&outer), vec![]);
+        assert_eq!(
+            inner.subtract(&HyperBox::new([5, 5, 5], [6, 6, 6])),
+            vec![inner]
+        );
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let mut set = BoxSet3::new();
+        set.add(BoundingBox::new(Point3::new(0, 0, 0), Point3::new(3, 3, 3)));
+        set.add(BoundingBox::new(Point3::new(1, 1, 1), Point3::new(4, 4, 4)));
+        assert_eq!(set.volume(), 27 + 27 - 8);
+        set.remove(BoundingBox::new(Point3::new(2, 2, 2), Point3::new(3, 3, 3)));
+        assert_eq!(set.volume(), 27 + 27 - 8 - 1);
+        assert!(!set.contains(Point3::new(2, 2, 2)));
+        assert!(set.contains(Point3::new(3, 3, 3)));
+        assert!(!set.contains(Point3::new(3, 0, 0)));
+        let clipped = set.clipped(BoundingBox::new(Point3::new(0, 0, 0), Point3::new(1, 1, 1)));
+        assert_eq!(clipped.volume(), 1);
+    }
+
+    #[test]
+    fn test_two_dimensions() {
+        let mut set = BoxSet::<i64, 2>::new();
+        set.add(HyperBox::from_inclusive([0, 0], [9, 9]));
+        set.remove(HyperBox::new([0, 0], [10, 1]));
+        set.add(HyperBox::new([-5, -5], [-4, -4]));
+        assert_eq!(set.volume(), 91);
+        assert_eq!(set.iter().count(), 2);
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::{BinaryHeap, HashSet};
+use std::hash::Hash;
+
+/// A partial solution to a maximization problem. To minimize, use `Reverse` values and bounds.
+pub trait BranchState: Clone + Eq + Hash {
+    type Value: Copy + Ord;
+    type Context: ?Sized;
+
+    /// The states one decision further along.
+    fn children(&self, context: &Self::Context) -> Vec<Self>;
+
+    /// The value if this is a complete solution, or None if it still needs more decisions.
+    fn value(&self, context: &Self::Context) -> Option<Self::Value>;
+
+    /// An optimistic estimate: no solution reachable from here, including this state itself,
+    /// may be worth more. Tighter bounds prune more.
+    fn upper_bound(&self, context: &Self::Context) -> Self::Value;
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Strategy {
+    /// Finds some solution quickly, then uses it to prune, with little memory.
+    DepthFirst,
+    /// Always expands the state with the highest bound, so it can stop as soon as nothing left
+    /// could beat the best so far.
+    BestFirst,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Optimum<S: BranchState> {
+    /// The best solution found and its value, or None if there were no complete solutions.
+    pub best: Option<(S::Value, S)>,
+    /// How many states had their children generated.
+    pub expanded: usize,
+    /// How many states were skipped because their bound couldn't beat the best so far.
+    pub pruned: usize,
+    /// How many states were skipped for having been seen already, when deduplicating.
+    pub duplicates: usize,
+}
+
+impl<S: BranchState> Optimum<S> {
+    pub fn value(&self) -> Option<S::Value> {
+        self.best.as_ref().map(|(value, _)| *value)
+    }
+}
+
+/// Either a stack or a priority queue of states, depending on the strategy.
+enum Pending<S: BranchState> {
+    Stack(Vec<S>),
+    Heap(BinaryHeap<(S::Value, usize)>, Vec<Option<S>>),
+}
+
+impl<S: BranchState> Pending<S> {
+    fn push(&mut self, state: S, bound: S::Value) {
+        match self {
+            Pending::Stack(stack) => stack.push(state),
+            Pending::Heap(heap, states) => {
+                heap.push((bound, states.len()));
+                states.push(Some(state));
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Option<S> {
+        match self {
+            Pending::Stack(stack) => stack.pop(),
+            Pending::Heap(heap, states) => heap.pop().and_then(|(_, idx)| states[idx].take()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Pending::Stack(stack) => stack.len(),
+            Pending::Heap(heap, _) => heap.len(),
+        }
+    }
+}
+
+/// Finds the most valuable complete solution reachable from `start`, skipping every state whose
+/// upper bound is no better than the best solution so far. With `dedup`, states that were already
+/// seen are skipped too, which only helps when different decision orders meet.
+pub fn maximize<S: BranchState>(
+    start: S,
+    context: &S::Context,
+    strategy: Strategy,
+    dedup: bool,
+) -> Optimum<S> {
+    let mut pending = match strategy {
+        Strategy::DepthFirst => Pending::Stack(Vec::new()),
+        Strategy::BestFirst => Pending::Heap(BinaryHeap::new(), Vec::new()),
+    };
+    let mut result = Optimum {
+        best: None,
+        expanded: 0,
+        pruned: 0,
+        duplicates: 0,
+    };
+    let mut seen = HashSet::new();
+
+    let start_bound = start.upper_bound(context);
+    pending.push(start, start_bound);
+    while let Some(state) = pending.pop() {
+        let bound = state.upper_bound(context);
+        if result.value().is_some_and(|best| bound <= best) {
+            result.pruned += 1;
+            if strategy == Strategy::BestFirst {
+                // Everything left has a bound at most this one
+                result.pruned += pending.len();
+                break;
+            }
+            continue;
+        }
+        if dedup && !seen.insert(state.clone()) {
+            result.duplicates += 1;
+            continue;
+        }
+        if let Some(value) = state.value(context) {
+            if result.value().map_or(true, |best| value > best) {
+                result.best = Some((value, state.clone()));
+            }
+        }
+        result.expanded += 1;
+        for child in state.children(context) {
+            let child_bound = child.upper_bound(context);
+            if result.value().is_some_and(|best| child_bound <= best) {
+                result.pruned += 1;
+            } else {
+                pending.push(child, child_bound);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use crate::branch_and_bound::{maximize, BranchState, Strategy};
+
+    /// A 0/1 knapsack, deciding on one item at a time.
+    #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+    struct Knapsack {
+        next_item: usize,
+        weight: u32,
+        value: u32,
+    }
+
+    struct Items {
+        capacity: u32,
+        /// (weight, value), sorted by value per weight descending
+        items: Vec<(u32, u32)>,
+    }
+
+    impl BranchState for Knapsack {
+        type Value = u32;
+        type Context = Items;
+
+        fn children(&self, context: &Items) -> Vec<Self> {
+            let Some((weight, value)) = context.items.get(self.next_item) else {
+                return vec![];
+            };
+            let skip = Knapsack {
+                next_item: self.next_item + 1,
+                ..self.clone()
+            };
+            let take = Knapsack {
+                next_item: self.next_item + 1,
+                weight: self.weight + weight,
+                value: self.value + value,
+            };
+            if take.weight <= context.capacity {
+                vec![skip, take]
+            } else {
+                vec![skip]
+            }
+        }
+
+        fn value(&self, context: &Items) -> Option<u32> {
+            (self.next_item == context.items.len()).then_some(self.value)
+        }
+
+        fn upper_bound(&self, context: &Items) -> u32 {
+            // The fractional relaxation
+            let mut room = context.capacity - self.weight;
+            let mut bound = self.value;
+            for (weight, value) in &context.items[self.next_item..] {
+                if *weight <= room {
+                    room -= weight;
+                    bound += value;
+                } else {
+                    bound += value * room / weight;
+                    break;
+                }
+            }
+            bound
+        }
+    }
+
+    #[test]
+    fn test_knapsack() {
+        let context = Items {
+            capacity: 50,
+            items: vec![(10, 60), (20, 100), (30, 120), (25, 70), (40, 80)],
+        };
+        let start = Knapsack {
+            next_item: 0,
+            weight: 0,
+            value: 0,
+        };
+        for strategy in [Strategy::DepthFirst, Strategy::BestFirst] {
+            for dedup in [false, true] {
+                let result = maximize(start.clone(), &context, strategy, dedup);
+                assert_eq!(result.value(), Some(220));
+                assert_eq!(result.best.as_ref().unwrap().1.weight, 50);
+                assert!(result.pruned > 0);
+                // Without pruning, there are up to 2^6 - 1 states
+                assert!(result.expanded < 63);
+            }
+        }
+
+        let empty = Items {
+            capacity: 0,
+            items: vec![],
+        };
+        let result = maximize(start, &empty, Strategy::DepthFirst, false);
+        assert_eq!(result.value(), Some(0));
+        assert_eq!(result.expanded, 1);
+    }
+}
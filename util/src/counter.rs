@@ -0,0 +1,259 @@
+use std::collections::{hash_map, HashMap};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use num_traits::{PrimInt, SaturatingMul, Unsigned};
+
+/// Unsigned integers usable as counts, e.g. `u64` or `u128`.
+pub trait CountValue: PrimInt + Unsigned + SaturatingMul + Debug {}
+
+impl<T: PrimInt + Unsigned + SaturatingMul + Debug> CountValue for T {}
+
+/// A multiset of keys. Zero counts are never stored. The plain operations panic on overflow, and
+/// each has a `checked_` variant that returns None instead and a `saturating_` variant that clamps.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Counter<K: Hash + Eq, C: CountValue = u64> {
+    counts: HashMap<K, C>,
+}
+
+impl<K: Hash + Eq, C: CountValue> Default for Counter<K, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, C: CountValue> Counter<K, C> {
+    pub fn new() -> Self {
+        Self {
+            counts: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> C {
+        self.counts.get(key).copied().unwrap_or_else(C::zero)
+    }
+
+    /// The number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, C)> {
+        self.counts.iter().map(|(k, c)| (k, *c))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.counts.keys()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = C> + '_ {
+        self.counts.values().copied()
+    }
+
+    /// Removes the key, returning its count.
+    pub fn remove(&mut self, key: &K) -> C {
+        self.counts.remove(key).unwrap_or_else(C::zero)
+    }
+
+    /// The sum of all counts.
+    pub fn total(&self) -> C {
+        self.checked_total().expect("Counter total overflowed")
+    }
+
+    pub fn checked_total(&self) -> Option<C> {
+        self.values()
+            .try_fold(C::zero(), |acc, c| acc.checked_add(&c))
+    }
+
+    /// Every key and count, largest count first.
+    pub fn most_common(&self) -> Vec<(&K, C)> {
+        let mut result: Vec<_> = self.iter().collect();
+        result.sort_by(|(_, a), (_, b)| b.cmp(a));
+        result
+    }
+
+    pub fn add(&mut self, key: K, count: C) {
+        self.checked_add(key, count)
+            .expect("Counter addition overflowed");
+    }
+
+    /// Returns the new count, or None (leaving the count unchanged) if it would overflow.
+    pub fn checked_add(&mut self, key: K, count: C) -> Option<C> {
+        self.combine(key, count, |a, b| a.checked_add(&b))
+    }
+
+    /// Returns the new count.
+    pub fn saturating_add(&mut self, key: K, count: C) -> C {
+        self.combine(key, count, |a, b| Some(a.saturating_add(b)))
+            .unwrap()
+    }
+
+    /// Adds every count from other into this.
+    pub fn merge(self, other: Self) -> Self {
+        self.checked_merge(other).expect("Counter merge overflowed")
+    }
+
+    pub fn checked_merge(mut self, other: Self) -> Option<Self> {
+        for (key, count) in other {
+            self.checked_add(key, count)?;
+        }
+        Some(self)
+    }
+
+    pub fn saturating_merge(mut self, other: Self) -> Self {
+        for (key, count) in other {
+            self.saturating_add(key, count);
+        }
+        self
+    }
+
+    /// Multiplies every count by factor.
+    pub fn scale(self, factor: C) -> Self {
+        self.checked_scale(factor)
+            .expect("Counter scaling overflowed")
+    }
+
+    pub fn checked_scale(self, factor: C) -> Option<Self> {
+        self.counts
+            .into_iter()
+            .map(|(key, count)| Some((key, count.checked_mul(&factor)?)))
+            .collect::<Option<HashMap<_, _>>>()
+            .map(Self::from)
+    }
+
+    pub fn saturating_scale(self, factor: C) -> Self {
+        self.counts
+            .into_iter()
+            .map(|(key, count)| (key, count.saturating_mul(&factor)))
+            .collect::<HashMap<_, _>>()
+            .into()
+    }
+
+    /// Renames every key, adding together the counts of keys that end up the same.
+    pub fn map_keys<K2: Hash + Eq>(self, mut f: impl FnMut(&K) -> K2) -> Counter<K2, C> {
+        self.flat_map_keys(|key| [f(key)])
+    }
+
+    /// Replaces every key with some number of new keys, each of which gets the old key's count.
+    /// Useful for stepping a population forward when only the number of each kind matters.
+    pub fn flat_map_keys<K2, I>(self, f: impl FnMut(&K) -> I) -> Counter<K2, C>
+    where
+        K2: Hash + Eq,
+        I: IntoIterator<Item = K2>,
+    {
+        self.checked_flat_map_keys(f)
+            .expect("Counter key mapping overflowed")
+    }
+
+    pub fn checked_flat_map_keys<K2, I>(self, mut f: impl FnMut(&K) -> I) -> Option<Counter<K2, C>>
+    where
+        K2: Hash + Eq,
+        I: IntoIterator<Item = K2>,
+    {
+        let mut result = Counter::new();
+        for (key, count) in self {
+            for new_key in f(&key) {
+                result.checked_add(new_key, count)?;
+            }
+        }
+        Some(result)
+    }
+
+    fn combine(&mut self, key: K, count: C, op: impl Fn(C, C) -> Option<C>) -> Option<C> {
+        if count.is_zero() {
+            return Some(self.get(&key));
+        }
+        match self.counts.entry(key) {
+            hash_map::Entry::Occupied(mut entry) => {
+                let new_count = op(*entry.get(), count)?;
+                entry.insert(new_count);
+                Some(new_count)
+            }
+            hash_map::Entry::Vacant(entry) => Some(*entry.insert(count)),
+        }
+    }
+}
+
+impl<K: Hash + Eq, C: CountValue> From<HashMap<K, C>> for Counter<K, C> {
+    fn from(mut counts: HashMap<K, C>) -> Self {
+        counts.retain(|_, count| !count.is_zero());
+        Self { counts }
+    }
+}
+
+impl<K: Hash + Eq, C: CountValue> From<Counter<K, C>> for HashMap<K, C> {
+    fn from(counter: Counter<K, C>) -> Self {
+        counter.counts
+    }
+}
+
+impl<K: Hash + Eq, C: CountValue> IntoIterator for Counter<K, C> {
+    type Item = (K, C);
+    type IntoIter = hash_map::IntoIter<K, C>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.counts.into_iter()
+    }
+}
+
+impl<K: Hash + Eq, C: CountValue> Extend<(K, C)> for Counter<K, C> {
+    fn extend<T: IntoIterator<Item = (K, C)>>(&mut self, iter: T) {
+        for (key, count) in iter {
+            self.add(key, count);
+        }
+    }
+}
+
+impl<K: Hash + Eq, C: CountValue> FromIterator<(K, C)> for Counter<K, C> {
+    fn from_iter<T: IntoIterator<Item = (K, C)>>(iter: T) -> Self {
+        let mut counter = Self::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::counter::Counter;
+    use crate::iter_helpers::IteratorHelpers;
+
+    #[test]
+    fn test_lanternfish() {
+        // 2021 day 6 example, where each fish is a countdown timer
+        let mut fish: Counter<u8> = [3, 4, 3, 1, 2].into_iter().into_counter();
+        for _ in 0..256 {
+            fish = fish.flat_map_keys(|&timer| match timer {
+                0 => vec![6, 8],
+                _ => vec![timer - 1],
+            });
+        }
+        assert_eq!(fish.total(), 26984457539);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let counter: Counter<char, u8> = [('a', 200), ('b', 100), ('a', 50)].into_iter().collect();
+        assert_eq!(counter.get(&'a'), 250);
+        assert_eq!(counter.checked_total(), None);
+        assert_eq!(counter.clone().checked_scale(2), None);
+
+        let saturated = counter.clone().saturating_scale(2);
+        assert_eq!(saturated.get(&'a'), 255);
+        assert_eq!(saturated.get(&'b'), 200);
+
+        assert_eq!(counter.clone().checked_flat_map_keys(|_| ['z']), None);
+        let mut is_a = counter.clone().map_keys(|key| *key == 'a');
+        assert_eq!(is_a.checked_add(true, 10), None);
+        assert_eq!(is_a.saturating_add(true, 10), u8::MAX);
+        assert_eq!(is_a.get(&false), 100);
+
+        assert_eq!(
+            counter.checked_merge([('a', 10)].into_iter().collect()),
+            None
+        );
+    }
+}
@@ -1,8 +1,9 @@
 /// Taken from https://math.stackexchange.com/a/3864593/1264446
+use std::collections::HashMap;
 use std::error::Error;
-use std::fmt::Debug;
+use std::hash::Hash;
 
-use num_traits::{Num, Signed};
+use num_traits::{Num, PrimInt};
 
 /// Extended Greatest Common Divisor Algorithm
 ///
@@ -12,7 +13,7 @@ use num_traits::{Num, Signed};
 ///
 /// Reference:
 ///     https://en.wikipedia.org/wiki/Extended_Euclidean_algorithm#Pseudocode
-fn extended_gcd<N: Num + Copy>(a: N, b: N) -> (N, N, N) {
+pub fn extended_gcd<N: Num + Copy>(a: N, b: N) -> (N, N, N) {
     let (mut old_r, mut r) = (a, b);
     let (mut old_s, mut s) = (N::one(), N::zero());
     let (mut old_t, mut t) = (N::zero(), N::one());
@@ -31,67 +32,363 @@ fn extended_gcd<N: Num + Copy>(a: N, b: N) -> (N, N, N) {
     (old_r, old_s, old_t)
 }
 
-/// Combine two phased rotations into a single phased rotation
+/// A congruence x ≡ residue (mod modulus).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Congruence<N> {
+    pub residue: N,
+    pub modulus: N,
+}
+
+impl<N: PrimInt> Congruence<N> {
+    pub fn new(residue: N, modulus: N) -> Self {
+        Self { residue, modulus }
+    }
+}
+
+/// Combine two congruences into a single one which holds if and only if both of them do.
 ///
-/// Returns: combined_period, combined_phase
+/// The moduli don't need to be coprime. Errors if the congruences can never both hold, or if the
+/// combined modulus doesn't fit in an i128.
+fn combine_congruences(a: (i128, i128), b: (i128, i128)) -> Result<(i128, i128), Box<dyn Error>> {
+    let ((a_residue, a_modulus), (b_residue, b_modulus)) = (a, b);
+    let (gcd, s, _t) = extended_gcd(a_modulus, b_modulus);
+    let difference = b_residue - a_residue;
+    if difference % gcd != 0 {
+        return Err("Congruences never hold at the same time.".into());
+    }
+    let combined_modulus = (a_modulus / gcd)
+        .checked_mul(b_modulus)
+        .ok_or("Combined modulus overflows i128")?;
+    // Solve a_residue + a_modulus * k ≡ b_residue (mod b_modulus) for k. Reducing both factors
+    // first keeps the product from overflowing as long as the moduli fit in an i64.
+    let reduced_modulus = b_modulus / gcd;
+    let k = ((difference / gcd).rem_euclid(reduced_modulus))
+        .checked_mul(s.rem_euclid(reduced_modulus))
+        .ok_or("Intermediate value overflows i128")?
+        % reduced_modulus;
+    let combined_residue = (a_residue + a_modulus * k).rem_euclid(combined_modulus);
+    Ok((combined_residue, combined_modulus))
+}
+
+/// Solves a system of congruences (the Chinese remainder theorem), returning a single congruence
+/// whose residue is the smallest non-negative solution.
 ///
-/// The combined rotation is at its reference point if and only if both a and b
-/// are at their reference points.
-fn combine_phased_rotations<N: Num + Copy>(
-    a_period: N,
-    a_phase: N,
-    b_period: N,
-    b_phase: N,
-) -> Result<(N, N), Box<dyn Error>> {
-    let (gcd, s, _t) = extended_gcd(a_period, b_period);
-    let phase_difference = a_phase - b_phase;
-    let (pd_mult, pd_remainder) = (phase_difference / gcd, phase_difference % gcd);
-
-    if pd_remainder != N::zero() {
-        return Err("Rotation reference points never synchronize.".into());
-    }
-
-    let combined_period = a_period / gcd * b_period;
-    let combined_phase = (a_phase - s * pd_mult * a_period) % combined_period;
-    Ok((combined_period, combined_phase))
-}
-
-pub trait FirstCommonCycle<N: Num + Copy + Signed> {
-    /// Takes a series of (first_occurrence, second_occurrence) pairs and returns the first time all the cycles align
-    fn find_first_common_cycle(self) -> N;
-}
-
-impl<N: Num + Copy + Signed + Debug + Ord + PartialOrd, I: Iterator<Item = (N, N)>>
-    FirstCommonCycle<N> for I
-{
-    fn find_first_common_cycle(self) -> N {
-        let (period, phase) = self.fold(
-            (N::one(), N::zero()),
-            |(existing_period, existing_phase), (new_offset, new_period)| {
-                combine_phased_rotations(existing_period, existing_phase, new_period, -new_offset)
-                    .unwrap()
-            },
-        );
-        eprintln!("{:?}", (period, phase));
-        if phase == N::zero() {
-            period
-        } else {
-            let mut result = -phase;
-            while result < N::zero() {
-                result = result + period;
+/// Errors if the congruences can never all hold or a value overflows. Moduli must be positive.
+pub fn chinese_remainder<N: PrimInt>(
+    congruences: impl IntoIterator<Item = Congruence<N>>,
+) -> Result<Congruence<N>, Box<dyn Error>> {
+    let (residue, modulus) = congruences.into_iter().try_fold(
+        (0i128, 1i128),
+        |existing, Congruence { residue, modulus }| {
+            let residue = to_i128(residue)?;
+            let modulus = to_i128(modulus)?;
+            if modulus <= 0 {
+                return Err(format!("Modulus must be positive, got {}", modulus).into());
             }
-            result
+            combine_congruences(existing, (residue.rem_euclid(modulus), modulus))
+        },
+    )?;
+    Ok(Congruence::new(from_i128(residue)?, from_i128(modulus)?))
+}
+
+fn to_i128<N: PrimInt>(value: N) -> Result<i128, Box<dyn Error>> {
+    value
+        .to_i128()
+        .ok_or_else(|| "Value doesn't fit in i128".into())
+}
+
+fn from_i128<N: PrimInt>(value: i128) -> Result<N, Box<dyn Error>> {
+    N::from(value).ok_or_else(|| format!("{} doesn't fit in the result type", value).into())
+}
+
+pub trait FirstCommonCycle<N> {
+    /// Takes a series of (first_occurrence, period) pairs and returns the first time all the
+    /// cycles align, which is never before any of the first occurrences.
+    ///
+    /// Errors if they never align or a value overflows.
+    fn find_first_common_cycle(self) -> Result<N, Box<dyn Error>>;
+}
+
+impl<N: PrimInt, I: Iterator<Item = (N, N)>> FirstCommonCycle<N> for I {
+    fn find_first_common_cycle(self) -> Result<N, Box<dyn Error>> {
+        let mut earliest = N::zero();
+        let congruences = self.map(|(offset, period)| {
+            earliest = earliest.max(offset);
+            Congruence::new(offset, period)
+        });
+        let Congruence { residue, modulus } = chinese_remainder(congruences)?;
+        if residue >= earliest {
+            return Ok(residue);
         }
+        // Round up to the first aligned time that's at or after every first occurrence
+        let cycles_needed = (earliest - residue - N::one()) / modulus + N::one();
+        cycles_needed
+            .checked_mul(&modulus)
+            .and_then(|skip| residue.checked_add(&skip))
+            .ok_or_else(|| "First common cycle overflows".into())
     }
 }
 
-pub fn find_first_common_cycle<N: Num + Copy + Signed>(pairs: impl Iterator<Item = (N, N)>) -> N {
-    let (period, phase) = pairs.fold(
-        (N::one(), N::zero()),
-        |(existing_period, existing_phase), (new_offset, new_period)| {
-            combine_phased_rotations(existing_period, existing_phase, new_period, -new_offset)
-                .unwrap()
-        },
-    );
-    -phase % period
+/// Where a sequence of states starts repeating: after `start` steps the states repeat every
+/// `length` steps (μ and λ in the usual terminology).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CycleBounds {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl CycleBounds {
+    /// The earliest step which has the same state as step n.
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// A cycle found by recording every state, so states and values can be looked up for any step.
+#[derive(Debug, Clone)]
+pub struct StateCycle<S> {
+    pub bounds: CycleBounds,
+    /// Every state up to and including the first repeated one, so `states[start + length]` is
+    /// equivalent to `states[start]`.
+    pub states: Vec<S>,
+}
+
+impl<S> StateCycle<S> {
+    /// The state after n steps.
+    pub fn state_at(&self, n: usize) -> &S {
+        &self.states[self.bounds.equivalent_step(n)]
+    }
+
+    /// The value after n steps, where the value may drift by a fixed amount each time around the
+    /// cycle (eg the height of a tower that repeats its shape). States that only match by
+    /// fingerprint are fine, as long as the value's drift is the same every cycle.
+    pub fn value_at(&self, n: usize, value: impl Fn(&S) -> i64) -> i64 {
+        let CycleBounds { start, length } = self.bounds;
+        if n < start {
+            return value(&self.states[n]);
+        }
+        let per_cycle = value(&self.states[start + length]) - value(&self.states[start]);
+        let cycles = ((n - start) / length) as i64;
+        value(self.state_at(n)) + cycles * per_cycle
+    }
+}
+
+/// Steps from the initial state until a state repeats, remembering every state along the way.
+pub fn find_cycle<S: Clone + Eq + Hash>(initial: S, step: impl FnMut(&S) -> S) -> StateCycle<S> {
+    find_cycle_by(initial, step, |s| s.clone())
+}
+
+/// Like `find_cycle`, but states are considered the same when their fingerprints match. Useful
+/// when only part of the state matters for what happens next, or the full state is expensive to
+/// hash.
+pub fn find_cycle_by<S, K: Eq + Hash>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    mut fingerprint: impl FnMut(&S) -> K,
+) -> StateCycle<S> {
+    let mut seen = HashMap::new();
+    let mut states = vec![initial];
+    loop {
+        let current = states.last().unwrap();
+        let index = states.len() - 1;
+        if let Some(start) = seen.insert(fingerprint(current), index) {
+            return StateCycle {
+                bounds: CycleBounds {
+                    start,
+                    length: index - start,
+                },
+                states,
+            };
+        }
+        let next = step(current);
+        states.push(next);
+    }
+}
+
+/// Finds the cycle with Brent's algorithm, which only keeps a couple of states around at a time
+/// at the cost of running the step function a few more times.
+pub fn find_cycle_bounds_brent<S: Clone + Eq>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+) -> CycleBounds {
+    // Find the length by racing a hare ahead of a tortoise which teleports to it at every power
+    // of two.
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    // Then find the start by moving two states that are `length` apart in lockstep.
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    CycleBounds { start, length }
+}
+
+#[cfg(test)]
+mod test {
+    use num_traits::PrimInt;
+
+    use crate::cycle_helpers::{
+        chinese_remainder, find_cycle, find_cycle_bounds_brent, find_cycle_by, Congruence,
+        CycleBounds, FirstCommonCycle,
+    };
+
+    #[test]
+    fn test_chinese_remainder() {
+        let solve = |congruences: &[(i64, i64)]| {
+            chinese_remainder(congruences.iter().map(|(r, m)| Congruence::new(*r, *m)))
+                .map(|c| (c.residue, c.modulus))
+                .ok()
+        };
+        assert_eq!(solve(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(solve(&[]), Some((0, 1)));
+        // Moduli with common factors, which agree on them
+        assert_eq!(solve(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(solve(&[(3, 6), (3, 9), (1, 2)]), Some((3, 18)));
+        // ...and which don't, since x would have to be both odd and even
+        assert_eq!(solve(&[(1, 4), (2, 6)]), None);
+        // Residues outside 0..modulus are reduced first
+        assert_eq!(solve(&[(-1, 5), (3, 7)]), Some((24, 35)));
+        assert_eq!(solve(&[(26, 5), (-11, 7)]), Some((31, 35)));
+        assert_eq!(solve(&[(1, 0)]), None);
+        assert_eq!(solve(&[(1, -3)]), None);
+
+        // The combined modulus doesn't fit in an i64
+        let big = 1i64 << 40;
+        assert_eq!(solve(&[(0, big), (1, big + 1)]), None);
+        // ...or an i128
+        let huge = 1i128 << 70;
+        let congruences = [Congruence::new(0, huge), Congruence::new(1, huge + 1)];
+        assert!(chinese_remainder(congruences).is_err());
+        // ...or the input doesn't
+        assert!(chinese_remainder([Congruence::new(0, u128::MAX)]).is_err());
+    }
+
+    fn first_common<N: PrimInt>(cycles: &[(N, N)]) -> Option<N> {
+        cycles.iter().copied().find_first_common_cycle().ok()
+    }
+
+    #[test]
+    fn test_first_common_cycle() {
+        assert_eq!(first_common(&[(7, 3)]), Some(7));
+        assert_eq!(first_common(&[(0, 4), (0, 6)]), Some(0));
+        assert_eq!(first_common(&[(5, 3), (1, 2)]), Some(5));
+        // 9 is the smallest solution, but it's before the first cycle starts at 13
+        assert_eq!(first_common(&[(13, 4), (3, 6)]), Some(21));
+        assert_eq!(first_common(&[(3, 6), (13, 4)]), Some(21));
+        assert_eq!(first_common(&[(1, 4), (2, 6)]), None);
+        // The residue fits, but rounding up to 257 doesn't
+        assert_eq!(first_common(&[(250u8, 7), (2, 3)]), None);
+        assert_eq!(first_common(&[(240u8, 7), (2, 3)]), Some(254));
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        // A hand-built rho: 0 -> 1 -> ... -> 9 -> 4
+        let rho = |n: &u32| if *n == 9 { 4 } else { n + 1 };
+        let cycle = find_cycle(0, rho);
+        let expected = CycleBounds {
+            start: 4,
+            length: 6,
+        };
+        assert_eq!(cycle.bounds, expected);
+        assert_eq!(cycle.states.len(), 11);
+        assert_eq!(*cycle.state_at(3), 3);
+        assert_eq!(*cycle.state_at(10), 4);
+        assert_eq!(*cycle.state_at(100), 4);
+        assert_eq!(*cycle.state_at(101), 5);
+        assert_eq!(find_cycle_bounds_brent(0, rho), expected);
+
+        // x^2 + 1 mod 10 from 3 goes 3, 0, 1, 2, 5, 6, 7, 0
+        let square_plus_one = |n: u64| move |x: &u64| (x * x + 1) % n;
+        let expected = CycleBounds {
+            start: 1,
+            length: 6,
+        };
+        assert_eq!(find_cycle(3, square_plus_one(10)).bounds, expected);
+        assert_eq!(find_cycle_bounds_brent(3, square_plus_one(10)), expected);
+
+        // A state that's already its own successor
+        let fixed = CycleBounds {
+            start: 0,
+            length: 1,
+        };
+        assert_eq!(find_cycle(7, |x: &u32| *x).bounds, fixed);
+        assert_eq!(find_cycle_bounds_brent(7, |x: &u32| *x), fixed);
+    }
+
+    #[test]
+    fn test_brent_agrees() {
+        for n in 2..300 {
+            for initial in [0, 1, 2, n / 2, n - 1] {
+                let step = |x: &u64| (x * x + 1) % n;
+                assert_eq!(
+                    find_cycle_bounds_brent(initial, step),
+                    find_cycle(initial, step).bounds,
+                    "x^2 + 1 mod {} from {}",
+                    n,
+                    initial
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_value_at() {
+        // Like stacking rocks, the phase repeats but the height keeps growing. Phases go
+        // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 3, each adding its own amount to the height.
+        let growth = [2, 5, 1, 3, 0, 4, 2];
+        let step = |&(phase, height): &(usize, i64)| {
+            let next = if phase == 6 { 3 } else { phase + 1 };
+            (next, height + growth[phase])
+        };
+        let cycle = find_cycle_by((0, 0), step, |(phase, _)| *phase);
+        assert_eq!(
+            cycle.bounds,
+            CycleBounds {
+                start: 3,
+                length: 4
+            }
+        );
+
+        let mut state = (0, 0);
+        for n in 0..100 {
+            assert_eq!(
+                cycle.value_at(n, |(_, height)| *height),
+                state.1,
+                "step {}",
+                n
+            );
+            assert_eq!(cycle.state_at(n).0, state.0);
+            state = step(&state);
+        }
+        // Far enough out that simulating would be slow, each cycle adds 9
+        assert_eq!(
+            cycle.value_at(3 + 4 * 1_000_000_000_000, |(_, height)| *height),
+            8 + 9 * 1_000_000_000_000
+        );
+    }
 }
@@ -0,0 +1,256 @@
+use std::collections::VecDeque;
+use std::hash::Hash;
+use std::ops::Sub;
+
+use num_traits::Zero;
+
+use crate::graph::Graph;
+
+/// A maximum matching between left items and right items, where `options[left]` lists the right
+/// items that left item may be matched with. Returns each left item's match, if it got one.
+/// Uses Hopcroft-Karp, so a unique perfect matching (like fields to columns) comes out directly
+/// without having to eliminate options one at a time.
+pub fn bipartite_matching(options: &[Vec<usize>], num_right: usize) -> Vec<Option<usize>> {
+    let mut matching = Matching {
+        options,
+        left: vec![None; options.len()],
+        right: vec![None; num_right],
+        layer: vec![usize::MAX; options.len()],
+    };
+    while matching.build_layers() {
+        for left in 0..options.len() {
+            if matching.left[left].is_none() {
+                matching.augment(left);
+            }
+        }
+    }
+    matching.left
+}
+
+struct Matching<'a> {
+    options: &'a [Vec<usize>],
+    left: Vec<Option<usize>>,
+    right: Vec<Option<usize>>,
+    /// BFS depth of each left item from the unmatched ones, or usize::MAX if unreachable.
+    layer: Vec<usize>,
+}
+
+impl<'a> Matching<'a> {
+    /// Returns whether any augmenting path exists.
+    fn build_layers(&mut self) -> bool {
+        let mut queue = VecDeque::new();
+        for (left, matched) in self.left.iter().enumerate() {
+            self.layer[left] = if matched.is_none() {
+                queue.push_back(left);
+                0
+            } else {
+                usize::MAX
+            };
+        }
+        let mut found = false;
+        while let Some(left) = queue.pop_front() {
+            for right in &self.options[left] {
+                match self.right[*right] {
+                    None => found = true,
+                    Some(other) if self.layer[other] == usize::MAX => {
+                        self.layer[other] = self.layer[left] + 1;
+                        queue.push_back(other);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        found
+    }
+
+    fn augment(&mut self, left: usize) -> bool {
+        for right in &self.options[left] {
+            let can_take = match self.right[*right] {
+                None => true,
+                Some(other) => self.layer[other] == self.layer[left] + 1 && self.augment(other),
+            };
+            if can_take {
+                self.left[left] = Some(*right);
+                self.right[*right] = Some(left);
+                return true;
+            }
+        }
+        // Dead end, don't try this one again in this phase
+        self.layer[left] = usize::MAX;
+        false
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MaxFlow<W> {
+    pub value: W,
+    /// The saturated edges from the source side to the sink side, whose capacities add up to the
+    /// value. Removing them disconnects the sink from the source.
+    pub cut_edges: Vec<(usize, usize)>,
+    /// Nodes still reachable from the source in the residual graph, in ascending order.
+    pub source_side: Vec<usize>,
+    /// Every other node, in ascending order.
+    pub sink_side: Vec<usize>,
+}
+
+/// The maximum flow from source to sink, treating edge weights as capacities, along with a
+/// minimum cut. An undirected graph's edges carry their capacity in each direction. Uses
+/// Edmonds-Karp, so it takes O(V * E^2) at worst, but far less with small integer capacities.
+pub fn max_flow<N, W>(graph: &Graph<N, W>, source: usize, sink: usize) -> MaxFlow<W>
+where
+    N: Hash + Eq + Clone,
+    W: Copy + Ord + Zero + Sub<Output = W>,
+{
+    assert_ne!(source, sink, "Source and sink must differ");
+    // Residual edges as (to, remaining capacity), each paired with its reverse at idx ^ 1
+    let mut residual: Vec<(usize, W)> = Vec::new();
+    let mut adjacent = vec![Vec::new(); graph.len()];
+    for from in graph.nodes() {
+        for (to, capacity) in graph.edges(from) {
+            adjacent[from].push(residual.len());
+            residual.push((*to, *capacity));
+            adjacent[*to].push(residual.len());
+            residual.push((from, W::zero()));
+        }
+    }
+
+    let mut value = W::zero();
+    let reachable = loop {
+        // Shortest augmenting path, as the residual edge used to reach each node
+        let mut via: Vec<Option<usize>> = vec![None; graph.len()];
+        let mut reachable = vec![false; graph.len()];
+        reachable[source] = true;
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            for edge in &adjacent[node] {
+                let (to, remaining) = residual[*edge];
+                if !reachable[to] && remaining > W::zero() {
+                    reachable[to] = true;
+                    via[to] = Some(*edge);
+                    queue.push_back(to);
+                }
+            }
+        }
+        if !reachable[sink] {
+            break reachable;
+        }
+
+        let mut path = Vec::new();
+        let mut node = sink;
+        while let Some(edge) = via[node] {
+            path.push(edge);
+            node = residual[edge ^ 1].0;
+        }
+        let bottleneck = path.iter().map(|edge| residual[*edge].1).min().unwrap();
+        for edge in path {
+            residual[edge].1 = residual[edge].1 - bottleneck;
+            residual[edge ^ 1].1 = residual[edge ^ 1].1 + bottleneck;
+        }
+        value = value + bottleneck;
+    };
+
+    let (source_side, sink_side) = graph.nodes().partition(|node| reachable[*node]);
+    let cut_edges = graph
+        .nodes()
+        .filter(|from| reachable[*from])
+        .flat_map(|from| {
+            graph
+                .neighbors(from)
+                .filter(|to| !reachable[*to])
+                .map(move |to| (from, to))
+        })
+        .collect();
+    MaxFlow {
+        value,
+        cut_edges,
+        source_side,
+        sink_side,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use crate::flow::{bipartite_matching, max_flow};
+    use crate::graph::Graph;
+
+    #[test]
+    fn test_matching() {
+        // Only one way to match everything, which greedy choices would miss
+        let options = vec![vec![0, 1], vec![0], vec![1, 2, 3], vec![2]];
+        assert_eq!(
+            bipartite_matching(&options, 4),
+            vec![Some(1), Some(0), Some(3), Some(2)]
+        );
+
+        let crowded = vec![vec![0], vec![0], vec![0, 1]];
+        let matched = bipartite_matching(&crowded, 2);
+        assert_eq!(matched.iter().flatten().count(), 2);
+        assert_eq!(matched[2], Some(1));
+    }
+
+    #[test]
+    fn test_max_flow() {
+        let mut pipes: Graph<char, u32> = Graph::directed();
+        for (from, to, capacity) in [
+            ('s', 'a', 10),
+            ('s', 'b', 5),
+            ('a', 'b', 15),
+            ('a', 't', 4),
+            ('b', 't', 10),
+        ] {
+            pipes.add_weighted_edge(from, to, capacity);
+        }
+        let s = pipes.index_of(&'s').unwrap();
+        let t = pipes.index_of(&'t').unwrap();
+        let flow = max_flow(&pipes, s, t);
+        assert_eq!(flow.value, 14);
+        let named = |nodes: &[usize]| nodes.iter().map(|n| *pipes.name(*n)).collect_vec();
+        assert_eq!(named(&flow.source_side), vec!['s', 'a', 'b']);
+        assert_eq!(named(&flow.sink_side), vec!['t']);
+        assert_eq!(flow.cut_edges.len(), 2);
+
+        // 2023 day 25 example, cutting the three wires splits the machine in two
+        let input = [
+            "jqt: rhn xhk nvd",
+            "rsh: frs pzl lsr",
+            "xhk: hfx",
+            "cmg: qnr nvd lhk bvb",
+            "rhn: xhk bvb hfx",
+            "bvb: xhk hfx",
+            "pzl: lsr hfx nvd",
+            "qnr: nvd",
+            "ntq: jqt hfx bvb xhk",
+            "nvd: lhk",
+            "lsr: lhk",
+            "rzs: qnr cmg lsr rsh",
+            "frs: qnr lhk lsr",
+        ];
+        let mut wires: Graph<&str, u32> = Graph::undirected();
+        for line in input {
+            let (from, tos) = line.split_once(": ").unwrap();
+            for to in tos.split(' ') {
+                wires.add_weighted_edge(from, to, 1);
+            }
+        }
+        let jqt = wires.index_of("jqt").unwrap();
+        let cmg = wires.index_of("cmg").unwrap();
+        let cut = max_flow(&wires, jqt, cmg);
+        assert_eq!(cut.value, 3);
+        assert_eq!(cut.cut_edges.len(), 3);
+        assert_eq!(cut.source_side.len() * cut.sink_side.len(), 54);
+        let mut cut_names = cut
+            .cut_edges
+            .iter()
+            .map(|(a, b)| {
+                [wires.name(*a), wires.name(*b)]
+                    .into_iter()
+                    .sorted()
+                    .join("/")
+            })
+            .collect_vec();
+        cut_names.sort();
+        assert_eq!(cut_names, vec!["bvb/cmg", "hfx/pzl", "jqt/nvd"]);
+    }
+}
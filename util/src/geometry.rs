@@ -0,0 +1,199 @@
+use itertools::Itertools;
+
+use crate::cycle_helpers::extended_gcd;
+use crate::point2::{Point, PointValue};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Containment {
+    Inside,
+    OnBoundary,
+    Outside,
+}
+
+/// A simple polygon whose vertices all lie on the integer lattice.
+///
+/// The vertices are the corners of a closed path; the edge from the last vertex back to the first
+/// is implied. Edges don't need to be axis-aligned. All math is done exactly, in i64/i128.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LatticePolygon {
+    vertices: Vec<Point<i64>>,
+}
+
+impl LatticePolygon {
+    /// Panics if any vertex doesn't fit in an i64.
+    pub fn new<PV: PointValue>(vertices: impl IntoIterator<Item = Point<PV>>) -> Self {
+        let mut vertices = vertices
+            .into_iter()
+            .map(|p| p.cast::<i64>().expect("Vertex out of range for i64"))
+            .collect_vec();
+        // Allow paths that explicitly return to their start
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Point<i64>] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Point<i64>, Point<i64>)> + '_ {
+        self.vertices.iter().copied().circular_tuple_windows()
+    }
+
+    /// Twice the signed area, via the shoelace formula. Positive when the vertices go
+    /// counter-clockwise with y pointing up (which is clockwise on screen, where y points down).
+    pub fn doubled_signed_area(&self) -> i128 {
+        self.edges()
+            .map(|(a, b)| a.x as i128 * b.y as i128 - b.x as i128 * a.y as i128)
+            .sum()
+    }
+
+    /// The enclosed area. Lattice polygons always have an area that's a multiple of 1/2, so this
+    /// panics if the area isn't a whole number; use `doubled_signed_area` for those cases.
+    pub fn area(&self) -> i128 {
+        let doubled = self.doubled_signed_area().abs();
+        assert!(doubled % 2 == 0, "Area is not integral: {}/2", doubled);
+        doubled / 2
+    }
+
+    /// Number of lattice points on the edges, counting each vertex once.
+    pub fn boundary_points(&self) -> i128 {
+        self.edges()
+            .map(|(a, b)| {
+                let delta = b - a;
+                extended_gcd(delta.dx as i128, delta.dy as i128).0.abs()
+            })
+            .sum()
+    }
+
+    /// Number of lattice points strictly inside the polygon, via Pick's theorem.
+    pub fn interior_points(&self) -> i128 {
+        // A = I + B/2 - 1  =>  I = (2A - B + 2) / 2
+        (self.doubled_signed_area().abs() - self.boundary_points() + 2) / 2
+    }
+
+    /// Number of lattice points inside or on the boundary. This is the "area" of the polygon when
+    /// the path runs through the centers of unit grid cells and those cells count too.
+    pub fn enclosed_points(&self) -> i128 {
+        self.interior_points() + self.boundary_points()
+    }
+
+    pub fn containment<PV: PointValue>(&self, point: &Point<PV>) -> Containment {
+        let p = point.cast::<i64>().expect("Point out of range for i64");
+        let (px, py) = (p.x as i128, p.y as i128);
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            let (ax, ay, bx, by) = (a.x as i128, a.y as i128, b.x as i128, b.y as i128);
+            let cross = (bx - ax) * (py - ay) - (by - ay) * (px - ax);
+            if cross == 0
+                && ax.min(bx) <= px
+                && px <= ax.max(bx)
+                && ay.min(by) <= py
+                && py <= ay.max(by)
+            {
+                return Containment::OnBoundary;
+            }
+            // Cast a ray towards +x and count the edges it crosses. Each edge is half-open in y
+            // so a ray passing exactly through a vertex is only counted once.
+            if (ay > py) != (by > py) {
+                // The crossing is to the right of the point iff the point is on the same side of
+                // the edge as the +x direction.
+                let crosses_right = if by > ay { cross > 0 } else { cross < 0 };
+                if crosses_right {
+                    inside = !inside;
+                }
+            }
+        }
+        if inside {
+            Containment::Inside
+        } else {
+            Containment::Outside
+        }
+    }
+
+    /// Whether the point is strictly inside the polygon.
+    pub fn contains<PV: PointValue>(&self, point: &Point<PV>) -> bool {
+        self.containment(point) == Containment::Inside
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::geometry::{Containment, LatticePolygon};
+    use crate::point2::{PointS, PointU};
+
+    fn square() -> LatticePolygon {
+        LatticePolygon::new([
+            PointS::new(0, 0),
+            PointS::new(4, 0),
+            PointS::new(4, 4),
+            PointS::new(0, 4),
+            PointS::new(0, 0),
+        ])
+    }
+
+    #[test]
+    fn test_square_counts() {
+        let square = square();
+        assert_eq!(square.vertices().len(), 4);
+        assert_eq!(square.doubled_signed_area(), 32);
+        assert_eq!(square.area(), 16);
+        assert_eq!(square.boundary_points(), 16);
+        assert_eq!(square.interior_points(), 9);
+        assert_eq!(square.enclosed_points(), 25);
+    }
+
+    #[test]
+    fn test_orientation_and_diagonals() {
+        let triangle =
+            LatticePolygon::new([PointS::new(0, 0), PointS::new(0, 3), PointS::new(3, 0)]);
+        assert_eq!(triangle.doubled_signed_area(), -9);
+        // Boundary: 3 + 3 on the axes, plus the diagonal's 3 points between the corners
+        assert_eq!(triangle.boundary_points(), 9);
+        assert_eq!(triangle.interior_points(), 1);
+    }
+
+    #[test]
+    fn test_containment() {
+        let square = square();
+        assert_eq!(square.containment(&PointU::new(2, 2)), Containment::Inside);
+        assert_eq!(
+            square.containment(&PointU::new(4, 2)),
+            Containment::OnBoundary
+        );
+        assert_eq!(
+            square.containment(&PointU::new(0, 0)),
+            Containment::OnBoundary
+        );
+        assert_eq!(square.containment(&PointU::new(5, 2)), Containment::Outside);
+        assert_eq!(
+            square.containment(&PointS::new(-1, 4)),
+            Containment::Outside
+        );
+
+        // Concave shape where rays pass through vertices
+        let u_shape = LatticePolygon::new([
+            PointS::new(0, 0),
+            PointS::new(6, 0),
+            PointS::new(6, 4),
+            PointS::new(4, 4),
+            PointS::new(4, 2),
+            PointS::new(2, 2),
+            PointS::new(2, 4),
+            PointS::new(0, 4),
+        ]);
+        assert!(u_shape.contains(&PointS::new(1, 3)));
+        assert!(u_shape.contains(&PointS::new(5, 3)));
+        assert!(!u_shape.contains(&PointS::new(3, 3)));
+        assert!(u_shape.contains(&PointS::new(3, 1)));
+        assert!(!u_shape.contains(&PointS::new(-1, 2)));
+        assert_eq!(
+            (1..6)
+                .flat_map(|x| (1..4).map(move |y| PointS::new(x, y)))
+                .filter(|p| u_shape.contains(p))
+                .count() as i128,
+            u_shape.interior_points()
+        );
+    }
+}
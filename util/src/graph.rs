@@ -0,0 +1,533 @@
+use std::borrow::Borrow;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+use itertools::Itertools;
+use num_traits::Zero;
+
+/// A graph whose nodes are named by `N` but stored and traversed by dense indices, in the order
+/// they were first seen. Edges carry a weight of type `W`, which is `()` for unweighted graphs.
+#[derive(Debug, Clone)]
+pub struct Graph<N: Hash + Eq + Clone = String, W: Clone = ()> {
+    directed: bool,
+    names: Vec<N>,
+    indices: HashMap<N, usize>,
+    out_edges: Vec<Vec<(usize, W)>>,
+    in_edges: Vec<Vec<(usize, W)>>,
+}
+
+impl<N: Hash + Eq + Clone, W: Clone> Graph<N, W> {
+    pub fn directed() -> Self {
+        Self::new(true)
+    }
+
+    /// Every edge added goes both ways.
+    pub fn undirected() -> Self {
+        Self::new(false)
+    }
+
+    fn new(directed: bool) -> Self {
+        Self {
+            directed,
+            names: Vec::new(),
+            indices: HashMap::new(),
+            out_edges: Vec::new(),
+            in_edges: Vec::new(),
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// The number of nodes.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// The index for the name, adding a node for it if it's new.
+    pub fn intern(&mut self, name: impl Into<N>) -> usize {
+        let name = name.into();
+        if let Some(idx) = self.indices.get(&name) {
+            return *idx;
+        }
+        self.names.push(name.clone());
+        self.out_edges.push(Vec::new());
+        self.in_edges.push(Vec::new());
+        self.indices.insert(name, self.names.len() - 1);
+        self.names.len() - 1
+    }
+
+    pub fn index_of<Q>(&self, name: &Q) -> Option<usize>
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.indices.get(name).copied()
+    }
+
+    pub fn name(&self, idx: usize) -> &N {
+        &self.names[idx]
+    }
+
+    pub fn names(&self) -> &[N] {
+        &self.names
+    }
+
+    pub fn nodes(&self) -> std::ops::Range<usize> {
+        0..self.len()
+    }
+
+    /// Interns both names and connects them, returning their indices.
+    pub fn add_weighted_edge(
+        &mut self,
+        from: impl Into<N>,
+        to: impl Into<N>,
+        weight: W,
+    ) -> (usize, usize) {
+        let from = self.intern(from);
+        let to = self.intern(to);
+        self.add_weighted_edge_by_index(from, to, weight);
+        (from, to)
+    }
+
+    pub fn add_weighted_edge_by_index(&mut self, from: usize, to: usize, weight: W) {
+        self.out_edges[from].push((to, weight.clone()));
+        self.in_edges[to].push((from, weight.clone()));
+        if !self.directed && from != to {
+            self.out_edges[to].push((from, weight.clone()));
+            self.in_edges[from].push((to, weight));
+        }
+    }
+
+    /// The edges leaving the node, as (target, weight).
+    pub fn edges(&self, idx: usize) -> &[(usize, W)] {
+        &self.out_edges[idx]
+    }
+
+    /// The edges arriving at the node, as (source, weight).
+    pub fn in_edges(&self, idx: usize) -> &[(usize, W)] {
+        &self.in_edges[idx]
+    }
+
+    pub fn neighbors(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        self.out_edges[idx].iter().map(|(to, _)| *to)
+    }
+
+    /// The nodes with an edge to this one.
+    pub fn predecessors(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        self.in_edges[idx].iter().map(|(from, _)| *from)
+    }
+
+    pub fn out_degree(&self, idx: usize) -> usize {
+        self.out_edges[idx].len()
+    }
+
+    pub fn in_degree(&self, idx: usize) -> usize {
+        self.in_edges[idx].len()
+    }
+
+    /// The weight of the first edge from one node to the other.
+    pub fn edge_weight(&self, from: usize, to: usize) -> Option<&W> {
+        self.out_edges[from]
+            .iter()
+            .find(|(target, _)| *target == to)
+            .map(|(_, weight)| weight)
+    }
+
+    /// The same graph with every edge flipped. Node indices are unchanged.
+    pub fn reversed(&self) -> Self {
+        Self {
+            out_edges: self.in_edges.clone(),
+            in_edges: self.out_edges.clone(),
+            ..self.clone()
+        }
+    }
+}
+
+impl<N: Hash + Eq + Clone, W: Clone + Default> Graph<N, W> {
+    /// Interns both names and connects them with a default weight, returning their indices.
+    pub fn add_edge(&mut self, from: impl Into<N>, to: impl Into<N>) -> (usize, usize) {
+        self.add_weighted_edge(from, to, W::default())
+    }
+
+    pub fn add_edge_by_index(&mut self, from: usize, to: usize) {
+        self.add_weighted_edge_by_index(from, to, W::default());
+    }
+}
+
+impl<N: Hash + Eq + Clone, W: Clone> Graph<N, W> {
+    /// Every node ordered so that edges only go forward, or Err with the nodes of some cycle in
+    /// edge order if there isn't one. Ties go to the lower index.
+    pub fn topological_sort(&self) -> Result<Vec<usize>, Vec<usize>> {
+        let mut in_degrees = self.nodes().map(|idx| self.in_degree(idx)).collect_vec();
+        let mut ready: BinaryHeap<_> = self
+            .nodes()
+            .filter(|idx| in_degrees[*idx] == 0)
+            .map(Reverse)
+            .collect();
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(Reverse(idx)) = ready.pop() {
+            order.push(idx);
+            for next in self.neighbors(idx) {
+                in_degrees[next] -= 1;
+                if in_degrees[next] == 0 {
+                    ready.push(Reverse(next));
+                }
+            }
+        }
+        if order.len() == self.len() {
+            return Ok(order);
+        }
+
+        // Every node left over has a predecessor that's also left over, so walking backwards
+        // through them must eventually repeat.
+        let mut position = vec![None; self.len()];
+        let mut walk = Vec::new();
+        let mut current = self.nodes().find(|idx| in_degrees[*idx] > 0).unwrap();
+        while position[current].is_none() {
+            position[current] = Some(walk.len());
+            walk.push(current);
+            current = self
+                .predecessors(current)
+                .find(|prev| in_degrees[*prev] > 0)
+                .unwrap();
+        }
+        let mut cycle = walk.split_off(position[current].unwrap());
+        cycle.reverse();
+        Err(cycle)
+    }
+
+    /// The strongly connected components, in topological order of the graph between them.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        // Kosaraju: finish order on the graph, then collect components on the reversed graph
+        let mut finished = Vec::with_capacity(self.len());
+        let mut visited = vec![false; self.len()];
+        for root in self.nodes() {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut stack = vec![(root, 0)];
+            while let Some((idx, edge)) = stack.pop() {
+                if let Some((next, _)) = self.out_edges[idx].get(edge) {
+                    stack.push((idx, edge + 1));
+                    if !visited[*next] {
+                        visited[*next] = true;
+                        stack.push((*next, 0));
+                    }
+                } else {
+                    finished.push(idx);
+                }
+            }
+        }
+
+        let mut assigned = vec![false; self.len()];
+        let mut components = Vec::new();
+        for root in finished.into_iter().rev() {
+            if assigned[root] {
+                continue;
+            }
+            assigned[root] = true;
+            let mut component = vec![root];
+            let mut stack = vec![root];
+            while let Some(idx) = stack.pop() {
+                for prev in self.predecessors(idx) {
+                    if !assigned[prev] {
+                        assigned[prev] = true;
+                        component.push(prev);
+                        stack.push(prev);
+                    }
+                }
+            }
+            component.sort_unstable();
+            components.push(component);
+        }
+        components
+    }
+
+    /// The number of edges on the shortest path from start to each node, if it's reachable.
+    pub fn bfs_distances(&self, start: usize) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.len()];
+        distances[start] = Some(0);
+        let mut frontier = VecDeque::from([start]);
+        while let Some(idx) = frontier.pop_front() {
+            let next_distance = distances[idx].map(|d| d + 1);
+            for next in self.neighbors(idx) {
+                if distances[next].is_none() {
+                    distances[next] = next_distance;
+                    frontier.push_back(next);
+                }
+            }
+        }
+        distances
+    }
+
+    /// `bfs_distances` from every node, indexed by [from][to]. Ignores weights.
+    pub fn all_pairs_bfs(&self) -> Vec<Vec<Option<usize>>> {
+        self.nodes().map(|idx| self.bfs_distances(idx)).collect()
+    }
+}
+
+impl<N: Hash + Eq + Clone, W: Copy + Ord + Zero> Graph<N, W> {
+    /// The cheapest total weight between every pair of nodes, indexed by [from][to]. Weights
+    /// may be negative as long as no cycle is.
+    pub fn floyd_warshall(&self) -> Vec<Vec<Option<W>>> {
+        let mut distances = vec![vec![None; self.len()]; self.len()];
+        for from in self.nodes() {
+            distances[from][from] = Some(W::zero());
+            for (to, weight) in self.edges(from) {
+                let current = &mut distances[from][*to];
+                if current.map_or(true, |d| *weight < d) {
+                    *current = Some(*weight);
+                }
+            }
+        }
+        for via in self.nodes() {
+            for from in self.nodes() {
+                let Some(first) = distances[from][via] else {
+                    continue;
+                };
+                for to in self.nodes() {
+                    let Some(second) = distances[via][to] else {
+                        continue;
+                    };
+                    let through = first + second;
+                    if distances[from][to].map_or(true, |d| through < d) {
+                        distances[from][to] = Some(through);
+                    }
+                }
+            }
+        }
+        distances
+    }
+
+    /// The heaviest path anywhere in the graph as (total weight, nodes), or Err with a cycle if
+    /// it isn't a DAG. Paths may start anywhere, so negative edges are only taken when they lead to
+    /// enough weight to make up for them, and if none do the path is a single node. An empty graph
+    /// gives a zero-weight empty path.
+    pub fn dag_longest_path(&self) -> Result<(W, Vec<usize>), Vec<usize>> {
+        let order = self.topological_sort()?;
+        let mut best: Vec<(W, Option<usize>)> = vec![(W::zero(), None); self.len()];
+        for idx in order.iter().copied() {
+            let (total, _) = best[idx];
+            for (next, weight) in self.edges(idx) {
+                let candidate = total + *weight;
+                // Starting at next is worth zero, so only heavier incoming paths replace that
+                if candidate > best[*next].0 {
+                    best[*next] = (candidate, Some(idx));
+                }
+            }
+        }
+        let Some(end) = order.iter().copied().max_by_key(|idx| best[*idx].0) else {
+            return Ok((W::zero(), Vec::new()));
+        };
+        let mut path = vec![end];
+        while let Some(prev) = best[*path.last().unwrap()].1 {
+            path.push(prev);
+        }
+        path.reverse();
+        Ok((best[end].0, path))
+    }
+}
+
+impl<N: Hash + Eq + Clone, W: Copy + Zero> Graph<N, W> {
+    /// Collapses corridors into single edges. A node is a corridor if `keep` rejects it and it
+    /// touches exactly two other nodes; every other node survives, and each walk along
+    /// corridors from one survivor to the next becomes a directed edge weighted by the total.
+    /// Walks follow edge directions, so one-way steps stay one-way, and walks that dead-end or
+    /// turn back are dropped. The result is always directed, with nodes in their original order.
+    pub fn contract(&self, keep: impl Fn(&N) -> bool) -> Graph<N, W> {
+        let is_corridor = self
+            .nodes()
+            .map(|idx| {
+                !keep(&self.names[idx])
+                    && self
+                        .neighbors(idx)
+                        .chain(self.predecessors(idx))
+                        .filter(|other| *other != idx)
+                        .unique()
+                        .count()
+                        == 2
+            })
+            .collect_vec();
+
+        let mut contracted = Graph::directed();
+        for idx in self.nodes().filter(|idx| !is_corridor[*idx]) {
+            contracted.intern(self.names[idx].clone());
+        }
+        for from in self.nodes().filter(|idx| !is_corridor[*idx]) {
+            'edges: for (first, first_weight) in self.edges(from) {
+                let (mut prev, mut current, mut total) = (from, *first, *first_weight);
+                // A corridor can only be walked once, so a longer walk must be stuck in a loop
+                for _ in 0..=self.len() {
+                    if !is_corridor[current] {
+                        let to = contracted.index_of(&self.names[current]).unwrap();
+                        let from = contracted.index_of(&self.names[from]).unwrap();
+                        contracted.add_weighted_edge_by_index(from, to, total);
+                        continue 'edges;
+                    }
+                    let Some((next, weight)) = self.edges(current).iter().find(|(n, _)| *n != prev)
+                    else {
+                        continue 'edges;
+                    };
+                    (prev, current, total) = (current, *next, total + *weight);
+                }
+            }
+        }
+        contracted
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use crate::graph::Graph;
+    use crate::grid::Grid;
+    use crate::point2::PointU;
+
+    #[test]
+    fn test_graph() {
+        let mut caves: Graph = Graph::undirected();
+        for line in ["start-A", "start-b", "A-c", "A-b", "b-d", "A-end", "b-end"] {
+            let (a, b) = line.split_once('-').unwrap();
+            caves.add_edge(a, b);
+        }
+        assert_eq!(caves.len(), 6);
+        let a = caves.index_of("A").unwrap();
+        assert_eq!(
+            caves.neighbors(a).map(|n| caves.name(n)).collect_vec(),
+            vec!["start", "c", "b", "end"]
+        );
+        assert_eq!(caves.in_degree(a), 4);
+
+        let mut orbits: Graph<&str, u32> = Graph::directed();
+        orbits.add_weighted_edge("COM", "B", 1);
+        orbits.add_weighted_edge("B", "C", 2);
+        orbits.add_weighted_edge("B", "D", 3);
+        let b = orbits.index_of("B").unwrap();
+        let d = orbits.index_of("D").unwrap();
+        assert_eq!(orbits.out_degree(b), 2);
+        assert_eq!(orbits.in_degree(b), 1);
+        assert_eq!(orbits.edge_weight(b, d), Some(&3));
+        assert_eq!(orbits.edge_weight(d, b), None);
+        let reversed = orbits.reversed();
+        assert_eq!(reversed.edge_weight(d, b), Some(&3));
+        assert_eq!(reversed.predecessors(b).collect_vec(), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut graph: Graph<&str> = Graph::directed();
+        for (from, to) in [("a", "b"), ("b", "c"), ("a", "d"), ("d", "c"), ("e", "a")] {
+            graph.add_edge(from, to);
+        }
+        let names = |order: Vec<usize>| order.into_iter().map(|n| *graph.name(n)).collect_vec();
+        assert_eq!(
+            names(graph.topological_sort().unwrap()),
+            vec!["e", "a", "b", "d", "c"]
+        );
+
+        let mut cyclic = graph.clone();
+        cyclic.add_edge("c", "e");
+        cyclic.add_edge("x", "a");
+        cyclic.add_edge("c", "y");
+        let cycle = cyclic.topological_sort().unwrap_err();
+        assert_eq!(cycle.len(), 4);
+        for (from, to) in cycle.iter().circular_tuple_windows() {
+            assert!(cyclic.neighbors(*from).contains(to));
+        }
+
+        let components = cyclic
+            .strongly_connected_components()
+            .into_iter()
+            .map(|c| {
+                c.into_iter()
+                    .map(|n| *cyclic.name(n))
+                    .sorted()
+                    .collect_vec()
+            })
+            .collect_vec();
+        assert_eq!(
+            components,
+            vec![vec!["x"], vec!["a", "b", "c", "d", "e"], vec!["y"]]
+        );
+    }
+
+    #[test]
+    fn test_distances() {
+        let mut graph: Graph<char, i64> = Graph::directed();
+        graph.add_weighted_edge('a', 'b', 4);
+        graph.add_weighted_edge('a', 'c', 1);
+        graph.add_weighted_edge('c', 'b', 2);
+        graph.add_weighted_edge('b', 'd', 5);
+        let [a, b, c, d] = ['a', 'b', 'c', 'd'].map(|n| graph.index_of(&n).unwrap());
+
+        let distances = graph.floyd_warshall();
+        assert_eq!(distances[a][b], Some(3));
+        assert_eq!(distances[a][d], Some(8));
+        assert_eq!(distances[d][a], None);
+
+        let hops = graph.all_pairs_bfs();
+        assert_eq!(hops[a][b], Some(1));
+        assert_eq!(hops[c][d], Some(2));
+        assert_eq!(hops[b][c], None);
+
+        assert_eq!(graph.dag_longest_path(), Ok((9, vec![a, b, d])));
+
+        // Starting after a negative edge beats taking it...
+        let mut graph: Graph<char, i64> = Graph::directed();
+        graph.add_weighted_edge('x', 'y', -5);
+        graph.add_weighted_edge('y', 'z', 8);
+        let [y, z] = ['y', 'z'].map(|n| graph.index_of(&n).unwrap());
+        assert_eq!(graph.dag_longest_path(), Ok((8, vec![y, z])));
+
+        // ...unless it leads somewhere better
+        graph.add_weighted_edge('w', 'x', 10);
+        let [w, x] = ['w', 'x'].map(|n| graph.index_of(&n).unwrap());
+        assert_eq!(graph.dag_longest_path(), Ok((13, vec![w, x, y, z])));
+
+        let mut downhill: Graph<char, i64> = Graph::directed();
+        downhill.add_weighted_edge('a', 'b', -1);
+        downhill.add_weighted_edge('b', 'c', -2);
+        let (weight, path) = downhill.dag_longest_path().unwrap();
+        assert_eq!(weight, 0);
+        assert_eq!(path.len(), 1);
+    }
+
+    #[test]
+    fn test_contract() {
+        let maze = Grid::<char>::from_str(
+            "#.###\n#...#\n#.#.#\n#...#\n###v#\n###.#",
+            "\n",
+            None,
+            |c| c.chars().next().unwrap(),
+        );
+        let start = PointU::new(1, 0);
+        let end = PointU::new(3, 5);
+        let graph = maze
+            .to_graph(|from, to| {
+                let downhill = to.y > from.y;
+                maze[from] != '#'
+                    && maze[to] != '#'
+                    && (downhill || (maze[from] != 'v' && maze[to] != 'v'))
+            })
+            .contract(|p| *p == start || *p == end);
+        let names = graph.names().to_vec();
+        assert_eq!(
+            names,
+            vec![start, PointU::new(1, 1), PointU::new(3, 3), end]
+        );
+        let [start, top, bottom, end] = [0, 1, 2, 3];
+        assert_eq!(graph.edges(top), &[(start, 1), (bottom, 4), (bottom, 4)]);
+        assert_eq!(graph.edges(bottom), &[(top, 4), (top, 4), (end, 2)]);
+        assert_eq!(graph.in_degree(end), 1);
+        assert_eq!(graph.out_degree(end), 0);
+    }
+}
@@ -6,6 +6,7 @@ use std::ops::{Index, IndexMut};
 use itertools::Itertools;
 use serde::de::DeserializeOwned;
 
+use crate::graph::Graph;
 use crate::point2::{Delta, DeltaU, Point, PointU, PointValue};
 
 #[derive(Clone, Hash, Eq, PartialEq)]
@@ -183,6 +184,20 @@ impl<T> Grid<T> {
     pub fn point_in_grid(&self, point: PointU, delta: &DeltaU) -> Option<PointU> {
         (point.checked_add(delta)).filter(|p| p.x < self.width && p.y < self.height)
     }
+
+    /// A directed graph with an edge of length 1 between each pair of orthogonal neighbors that
+    /// `can_step(from, to)` allows. Points with no edges either way are left out.
+    pub fn to_graph(&self, can_step: impl Fn(PointU, PointU) -> bool) -> Graph<PointU, usize> {
+        let mut graph = Graph::directed();
+        for from in self.points() {
+            for to in self.neighbors(from, Neighbors::Four) {
+                if can_step(from, to) {
+                    graph.add_weighted_edge(from, to, 1);
+                }
+            }
+        }
+        graph
+    }
 }
 
 impl<T: Debug> Debug for Grid<T> {
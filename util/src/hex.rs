@@ -0,0 +1,339 @@
+use std::str::FromStr;
+
+use crate::point2::PointS;
+
+/// A hex tile position (or offset between positions) in cube coordinates, where q + r + s == 0.
+/// Only q and r are stored; s is derived.
+///
+/// When converted to a `PointS`, q is the x axis and r is the y axis ("axial" coordinates). With
+/// pointy-topped hexes, +q is east and +r is south-east. With flat-topped hexes, +q is south-east
+/// and +r is south.
+#[derive(Hash, Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Debug)]
+pub struct Hex {
+    pub q: i32,
+    pub r: i32,
+}
+
+impl_op_ex!(+ |a: &Hex, b: &Hex| -> Hex { Hex::new(a.q + b.q, a.r + b.r) });
+impl_op_ex!(-|a: &Hex, b: &Hex| -> Hex { Hex::new(a.q - b.q, a.r - b.r) });
+impl_op_ex!(*|a: &Hex, b: i32| -> Hex { Hex::new(a.q * b, a.r * b) });
+impl_op!(+= |a: &mut Hex, b: Hex| { *a = *a + b });
+impl_op!(+= |a: &mut Hex, b: &Hex| { *a = *a + b });
+impl_op!(-= |a: &mut Hex, b: Hex| { *a = *a - b });
+impl_op!(-= |a: &mut Hex, b: &Hex| { *a = *a - b });
+
+impl Hex {
+    pub const ORIGIN: Hex = Hex::new(0, 0);
+
+    /// The six neighbor offsets, going counter-clockwise.
+    pub const NEIGHBORS: [Hex; 6] = [
+        Hex::new(1, 0),
+        Hex::new(1, -1),
+        Hex::new(0, -1),
+        Hex::new(-1, 0),
+        Hex::new(-1, 1),
+        Hex::new(0, 1),
+    ];
+
+    pub const fn new(q: i32, r: i32) -> Hex {
+        Hex { q, r }
+    }
+
+    pub const fn from_cube(q: i32, r: i32, s: i32) -> Hex {
+        assert!(q + r + s == 0);
+        Hex { q, r }
+    }
+
+    pub fn s(&self) -> i32 {
+        -self.q - self.r
+    }
+
+    pub fn neighbors(&self) -> impl Iterator<Item = Hex> + '_ {
+        Hex::NEIGHBORS.iter().map(move |delta| self + delta)
+    }
+
+    /// Number of steps from the origin.
+    pub fn l1_dist(&self) -> i32 {
+        (self.q.abs() + self.r.abs() + self.s().abs()) / 2
+    }
+
+    pub fn dist(&self, other: &Hex) -> i32 {
+        (self - other).l1_dist()
+    }
+
+    /// Returns a new hex rotated counter-clockwise (as drawn with y pointing down) by the given
+    /// number of 60 degree steps. Negative steps rotate clockwise.
+    pub fn rotate_about_origin(&self, sixths: i32) -> Hex {
+        let mut cube = (self.q, self.r, self.s());
+        for _ in 0..sixths.rem_euclid(6) {
+            let (q, r, s) = cube;
+            cube = (-s, -q, -r);
+        }
+        Hex::new(cube.0, cube.1)
+    }
+
+    pub fn rotate_about(&self, center: &Hex, sixths: i32) -> Hex {
+        (self - center).rotate_about_origin(sixths) + center
+    }
+
+    /// All hexes exactly `radius` steps away, going counter-clockwise.
+    pub fn ring(&self, radius: i32) -> Vec<Hex> {
+        if radius == 0 {
+            return vec![*self];
+        }
+        let mut result = Vec::new();
+        let mut current = self + Hex::NEIGHBORS[4] * radius;
+        for side in Hex::NEIGHBORS {
+            for _ in 0..radius {
+                result.push(current);
+                current += side;
+            }
+        }
+        result
+    }
+
+    /// All hexes at most `radius` steps away.
+    pub fn within(&self, radius: i32) -> impl Iterator<Item = Hex> + '_ {
+        (-radius..=radius).flat_map(move |dq| {
+            let r_range = (-radius).max(-dq - radius)..=radius.min(-dq + radius);
+            r_range.map(move |dr| self + Hex::new(dq, dr))
+        })
+    }
+
+    pub fn to_point(&self) -> PointS {
+        PointS::new(self.q, self.r)
+    }
+
+    pub fn from_point(point: &PointS) -> Hex {
+        Hex::new(point.x, point.y)
+    }
+}
+
+/// Directions between pointy-topped hexes, which are in east/west rows.
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum PointyDirection {
+    E,
+    NE,
+    NW,
+    W,
+    SW,
+    SE,
+}
+
+/// Directions between flat-topped hexes, which are in north/south columns.
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum FlatDirection {
+    N,
+    NE,
+    SE,
+    S,
+    SW,
+    NW,
+}
+
+impl PointyDirection {
+    pub const ALL: [Self; 6] = [Self::E, Self::NE, Self::NW, Self::W, Self::SW, Self::SE];
+
+    pub fn delta(&self) -> Hex {
+        match self {
+            Self::E => Hex::new(1, 0),
+            Self::NE => Hex::new(1, -1),
+            Self::NW => Hex::new(0, -1),
+            Self::W => Hex::new(-1, 0),
+            Self::SW => Hex::new(-1, 1),
+            Self::SE => Hex::new(0, 1),
+        }
+    }
+
+    /// Parses a run of directions with no separators, eg `esenee`.
+    pub fn parse_path(s: &str) -> Vec<Self> {
+        parse_path(s)
+    }
+}
+
+impl FlatDirection {
+    pub const ALL: [Self; 6] = [Self::N, Self::NE, Self::SE, Self::S, Self::SW, Self::NW];
+
+    pub fn delta(&self) -> Hex {
+        match self {
+            Self::N => Hex::new(0, -1),
+            Self::NE => Hex::new(1, -1),
+            Self::SE => Hex::new(1, 0),
+            Self::S => Hex::new(0, 1),
+            Self::SW => Hex::new(-1, 1),
+            Self::NW => Hex::new(-1, 0),
+        }
+    }
+
+    /// Parses a run of directions with no separators, eg `nnesw`.
+    pub fn parse_path(s: &str) -> Vec<Self> {
+        parse_path(s)
+    }
+}
+
+impl FromStr for PointyDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "e" => Ok(Self::E),
+            "ne" => Ok(Self::NE),
+            "nw" => Ok(Self::NW),
+            "w" => Ok(Self::W),
+            "sw" => Ok(Self::SW),
+            "se" => Ok(Self::SE),
+            _ => Err(format!("Unknown pointy hex direction {}", s)),
+        }
+    }
+}
+
+impl FromStr for FlatDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "n" => Ok(Self::N),
+            "ne" => Ok(Self::NE),
+            "se" => Ok(Self::SE),
+            "s" => Ok(Self::S),
+            "sw" => Ok(Self::SW),
+            "nw" => Ok(Self::NW),
+            _ => Err(format!("Unknown flat hex direction {}", s)),
+        }
+    }
+}
+
+/// Greedily takes two-character directions first. That's never ambiguous, since n and s aren't
+/// directions on their own for pointy hexes, and e and w aren't for flat hexes.
+fn parse_path<D: FromStr>(s: &str) -> Vec<D> {
+    let mut result = Vec::new();
+    let mut rest = s.trim();
+    while !rest.is_empty() {
+        let two = rest.get(..2).and_then(|d| d.parse().ok());
+        if let Some(direction) = two {
+            result.push(direction);
+            rest = &rest[2..];
+        } else {
+            result.push(
+                rest[..1]
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Unknown hex direction at {}", rest)),
+            );
+            rest = &rest[1..];
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use crate::hex::{FlatDirection, Hex, PointyDirection};
+
+    #[test]
+    fn test_rotation() {
+        let hexes = [Hex::new(3, -1), Hex::new(-2, 5), Hex::new(0, 4)];
+        for hex in hexes {
+            let mut rotated = hex;
+            for _ in 0..6 {
+                rotated = rotated.rotate_about_origin(1);
+                assert_eq!(rotated.l1_dist(), hex.l1_dist());
+            }
+            assert_eq!(rotated, hex);
+            assert_eq!(hex.rotate_about_origin(6), hex);
+            assert_eq!(hex.rotate_about_origin(1).rotate_about_origin(-1), hex);
+            assert_eq!(hex.rotate_about_origin(2), hex.rotate_about_origin(-4));
+        }
+        assert_eq!(Hex::new(1, 0).rotate_about_origin(1), Hex::NEIGHBORS[1]);
+        let center = Hex::new(2, 2);
+        assert_eq!(
+            (center + Hex::new(1, 0)).rotate_about(&center, 3),
+            center + Hex::new(-1, 0)
+        );
+    }
+
+    #[test]
+    fn test_ring_and_within() {
+        let center = Hex::new(-3, 7);
+        assert_eq!(center.ring(0), vec![center]);
+        for radius in 1..6 {
+            let ring = center.ring(radius);
+            assert_eq!(ring.len(), 6 * radius as usize);
+            assert!(ring.iter().all_unique());
+            assert!(ring.iter().all(|h| h.dist(&center) == radius));
+
+            let within = center.within(radius).collect_vec();
+            assert_eq!(within.len(), (3 * radius * (radius + 1) + 1) as usize);
+            assert!(within.iter().all_unique());
+            assert!(within.iter().all(|h| h.dist(&center) <= radius));
+        }
+    }
+
+    #[test]
+    fn test_dist() {
+        let a = Hex::new(1, 2);
+        let b = Hex::new(-2, 1);
+        assert_eq!(a.dist(&b), 4);
+        assert_eq!(b.dist(&a), 4);
+        assert_eq!(a.dist(&a), 0);
+        assert_eq!(Hex::new(2, -5).l1_dist(), 5);
+        assert_eq!(Hex::from_cube(3, 0, -3).dist(&Hex::ORIGIN), 3);
+        for neighbor in Hex::ORIGIN.neighbors() {
+            assert_eq!(neighbor.dist(&Hex::ORIGIN), 1);
+        }
+    }
+
+    #[test]
+    fn test_parse_path() {
+        // 2020 day 24 examples
+        let walk = |path: &str| {
+            PointyDirection::parse_path(path)
+                .iter()
+                .fold(Hex::ORIGIN, |hex, d| hex + d.delta())
+        };
+        assert_eq!(
+            PointyDirection::parse_path("esenee"),
+            vec![
+                PointyDirection::E,
+                PointyDirection::SE,
+                PointyDirection::NE,
+                PointyDirection::E
+            ]
+        );
+        assert_eq!(walk("esenee"), Hex::new(3, 0));
+        assert_eq!(walk("nwwswee"), Hex::ORIGIN);
+
+        let walk = |path: &str| {
+            FlatDirection::parse_path(path)
+                .iter()
+                .fold(Hex::ORIGIN, |hex, d| hex + d.delta())
+        };
+        // n and s only stand alone when they aren't followed by e or w
+        assert_eq!(
+            FlatDirection::parse_path("nwnsesw"),
+            vec![
+                FlatDirection::NW,
+                FlatDirection::N,
+                FlatDirection::SE,
+                FlatDirection::SW
+            ]
+        );
+        assert_eq!(walk("nwnsesw"), FlatDirection::NW.delta());
+        assert_eq!(walk("nsenws"), Hex::ORIGIN);
+        assert_eq!(walk("nnnsss"), Hex::ORIGIN);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_pointy_invalid() {
+        PointyDirection::parse_path("enx");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_flat_invalid() {
+        // ne is fine, but a lone e is only a direction for pointy hexes
+        FlatDirection::parse_path("nee");
+    }
+}
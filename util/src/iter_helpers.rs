@@ -1,6 +1,10 @@
 use num_traits::Num;
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
 use std::hash::Hash;
+use std::ops::Sub;
+
+use crate::counter::{CountValue, Counter};
 
 pub trait IteratorHelpers: Iterator {
     fn into_count_map<I>(self) -> HashMap<Self::Item, usize>
@@ -28,6 +32,252 @@ pub trait IteratorHelpers: Iterator {
         }
         sum_map
     }
+
+    /// Like `into_count_map`, but as a `Counter` with the given count type.
+    fn into_counter<C>(self) -> Counter<Self::Item, C>
+    where
+        Self: Sized,
+        Self::Item: Hash + Eq,
+        C: CountValue,
+    {
+        self.map(|item| (item, C::one())).collect()
+    }
+
+    /// Like `into_sum_map`, but as a `Counter`. Panics on overflow.
+    fn into_sum_counter<K, C>(self) -> Counter<K, C>
+    where
+        Self: Iterator<Item = (K, C)> + Sized,
+        K: Hash + Eq,
+        C: CountValue,
+    {
+        self.collect()
+    }
+
+    /// Groups items by key, keeping them in their original order within each group.
+    fn into_grouped_map<K, F>(self, mut key: F) -> HashMap<K, Vec<Self::Item>>
+    where
+        Self: Sized,
+        K: Hash + Eq,
+        F: FnMut(&Self::Item) -> K,
+    {
+        let mut grouped_map: HashMap<K, Vec<Self::Item>> = HashMap::new();
+        for item in self {
+            grouped_map.entry(key(&item)).or_default().push(item);
+        }
+        grouped_map
+    }
+
+    /// The k items with the largest keys, largest first. Ties keep their original order.
+    fn top_k_by_key<K, F>(self, k: usize, mut key: F) -> Vec<Self::Item>
+    where
+        Self: Sized,
+        K: Ord,
+        F: FnMut(&Self::Item) -> K,
+    {
+        let mut keyed: Vec<_> = self.map(|item| (key(&item), item)).collect();
+        keyed.sort_by(|(a, _), (b, _)| b.cmp(a));
+        keyed.into_iter().take(k).map(|(_, item)| item).collect()
+    }
+
+    /// Collapses runs of equal adjacent items into (item, run length).
+    fn run_length_encode(self) -> Vec<(Self::Item, usize)>
+    where
+        Self: Sized,
+        Self::Item: PartialEq,
+    {
+        let mut runs: Vec<(Self::Item, usize)> = Vec::new();
+        for item in self {
+            match runs.last_mut() {
+                Some((last, count)) if *last == item => *count += 1,
+                _ => runs.push((item, 1)),
+            }
+        }
+        runs
+    }
+
+    /// Every run of `size` consecutive items, as owned Vecs. Panics if size is 0.
+    fn sliding_windows(self, size: usize) -> SlidingWindows<Self>
+    where
+        Self: Sized,
+        Self::Item: Clone,
+    {
+        assert!(size > 0, "Window size must be positive");
+        SlidingWindows {
+            iter: self,
+            size,
+            window: VecDeque::with_capacity(size),
+        }
+    }
+
+    /// `next - prev` for each adjacent pair of items.
+    fn pairwise_differences(self) -> PairwiseDifferences<Self>
+    where
+        Self: Sized,
+        Self::Item: Clone + Sub,
+    {
+        PairwiseDifferences {
+            iter: self,
+            prev: None,
+        }
+    }
+
+    /// The smallest and largest items, or None if there are none.
+    fn min_max(self) -> Option<(Self::Item, Self::Item)>
+    where
+        Self: Sized,
+        Self::Item: PartialOrd + Clone,
+    {
+        self.fold(None, |acc, item| match acc {
+            None => Some((item.clone(), item)),
+            Some((min, max)) => {
+                if item < min {
+                    Some((item, max))
+                } else if item > max {
+                    Some((min, item))
+                } else {
+                    Some((min, max))
+                }
+            }
+        })
+    }
+
+    /// The index of the first smallest item.
+    fn argmin(self) -> Option<usize>
+    where
+        Self: Sized,
+        Self::Item: Ord,
+    {
+        first_index_of_best(self, Ordering::Less)
+    }
+
+    /// The index of the first largest item.
+    fn argmax(self) -> Option<usize>
+    where
+        Self: Sized,
+        Self::Item: Ord,
+    {
+        first_index_of_best(self, Ordering::Greater)
+    }
+
+    fn argmin_by_key<K, F>(self, mut key: F) -> Option<usize>
+    where
+        Self: Sized,
+        K: Ord,
+        F: FnMut(&Self::Item) -> K,
+    {
+        first_index_of_best(self.map(|item| key(&item)), Ordering::Less)
+    }
+
+    fn argmax_by_key<K, F>(self, mut key: F) -> Option<usize>
+    where
+        Self: Sized,
+        K: Ord,
+        F: FnMut(&Self::Item) -> K,
+    {
+        first_index_of_best(self.map(|item| key(&item)), Ordering::Greater)
+    }
 }
 
 impl<T: ?Sized> IteratorHelpers for T where T: Iterator {}
+
+/// The index of the first item that beats every other item in the `better` direction.
+fn first_index_of_best<I>(iter: I, better: Ordering) -> Option<usize>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    let mut best: Option<(usize, I::Item)> = None;
+    for (idx, item) in iter.enumerate() {
+        if !matches!(&best, Some((_, best_item)) if item.cmp(best_item) != better) {
+            best = Some((idx, item));
+        }
+    }
+    best.map(|(idx, _)| idx)
+}
+
+pub struct SlidingWindows<I: Iterator> {
+    iter: I,
+    size: usize,
+    window: VecDeque<I::Item>,
+}
+
+impl<I: Iterator> Iterator for SlidingWindows<I>
+where
+    I::Item: Clone,
+{
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.window.len() == self.size {
+            self.window.pop_front();
+        }
+        while self.window.len() < self.size {
+            self.window.push_back(self.iter.next()?);
+        }
+        Some(self.window.iter().cloned().collect())
+    }
+}
+
+pub struct PairwiseDifferences<I: Iterator> {
+    iter: I,
+    prev: Option<I::Item>,
+}
+
+impl<I: Iterator> Iterator for PairwiseDifferences<I>
+where
+    I::Item: Clone + Sub,
+{
+    type Item = <I::Item as Sub>::Output;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.prev.is_none() {
+            self.prev = Some(self.iter.next()?);
+        }
+        let next = self.iter.next()?;
+        let prev = self.prev.replace(next.clone())?;
+        Some(next - prev)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::iter_helpers::IteratorHelpers;
+
+    #[test]
+    fn test_sequences() {
+        assert_eq!(
+            "aaabccdaa".chars().run_length_encode(),
+            vec![('a', 3), ('b', 1), ('c', 2), ('d', 1), ('a', 2)]
+        );
+        assert_eq!(
+            [1, 2, 3, 4]
+                .into_iter()
+                .sliding_windows(3)
+                .collect::<Vec<_>>(),
+            vec![vec![1, 2, 3], vec![2, 3, 4]]
+        );
+        assert_eq!([1, 2].into_iter().sliding_windows(3).count(), 0);
+        assert_eq!(
+            [7, 6, 4, 2, 1]
+                .iter()
+                .pairwise_differences()
+                .collect::<Vec<_>>(),
+            vec![-1, -2, -2, -1]
+        );
+        assert_eq!([5].into_iter().pairwise_differences().count(), 0);
+    }
+
+    #[test]
+    fn test_selection() {
+        let values = [3, 1, 4, 1, 5, 9, 2, 6, 5];
+        assert_eq!(values.iter().min_max(), Some((&1, &9)));
+        assert_eq!(Vec::<i32>::new().into_iter().min_max(), None);
+        assert_eq!(values.iter().argmin(), Some(1));
+        assert_eq!(values.iter().argmax(), Some(5));
+        assert_eq!(values.iter().argmax_by_key(|v| **v % 5), Some(2));
+        assert_eq!(values.iter().top_k_by_key(3, |v| **v), vec![&9, &6, &5]);
+        let by_parity = values.into_iter().into_grouped_map(|v| v % 2);
+        assert_eq!(by_parity[&0], vec![4, 2, 6]);
+        assert_eq!(by_parity[&1], vec![3, 1, 1, 5, 9, 5]);
+    }
+}
@@ -4,13 +4,29 @@ extern crate auto_ops;
 use itertools::{Itertools, MinMaxResult};
 
 pub mod additional_num_traits;
+pub mod bitset;
+pub mod box_set;
+pub mod branch_and_bound;
+pub mod counter;
 pub mod cycle_helpers;
+pub mod flow;
+pub mod geometry;
+pub mod graph;
 pub mod grid;
+pub mod hex;
 pub mod iter_helpers;
+pub mod line;
+pub mod linear_algebra;
 pub mod main_helpers;
+pub mod memo;
+pub mod number_theory;
+pub mod path_search;
 pub mod point;
 pub mod point2;
 pub mod point3;
+pub mod polynomial;
+pub mod radix;
+pub mod search;
 mod serde_helpers;
 
 #[allow(dead_code)]
@@ -0,0 +1,325 @@
+use std::array;
+use std::fmt::Debug;
+
+use num::rational::Ratio;
+use num::{FromPrimitive, Integer, One, Signed, ToPrimitive, Zero};
+
+/// Integer types that lines can be built from. Intersections are reported as `Ratio`s over the
+/// same type, so use `i128` for most things and `BigInt` when even that might overflow.
+pub trait ExactValue: Integer + Signed + Clone + FromPrimitive + Debug {}
+
+impl<T: Integer + Signed + Clone + FromPrimitive + Debug> ExactValue for T {}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LineKind {
+    /// Extends forever in both directions
+    Line,
+    /// Starts at the origin and extends forever along the direction
+    Ray,
+    /// Starts at the origin and ends at origin + direction
+    Segment,
+}
+
+/// The points `origin + t * direction`, with t restricted by the kind of line.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Line<T: ExactValue, const N: usize> {
+    pub origin: [T; N],
+    pub direction: [T; N],
+    pub kind: LineKind,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum IntersectionResult<T: ExactValue, const N: usize> {
+    None,
+    /// Meet at exactly one point, where `t` is the parameter along self and `s` along the arg.
+    Point {
+        point: [Ratio<T>; N],
+        t: Ratio<T>,
+        s: Ratio<T>,
+    },
+    /// Collinear and sharing more than one point. The overlap is the range of `t` along self, with
+    /// None meaning it's unbounded on that side.
+    Overlap {
+        start: Option<Ratio<T>>,
+        end: Option<Ratio<T>>,
+    },
+}
+
+impl<T: ExactValue, const N: usize> Line<T, N> {
+    /// Panics if the direction is zero or any value doesn't fit in T.
+    pub fn new<V: ToPrimitive>(
+        origin: impl Into<[V; N]>,
+        direction: impl Into<[V; N]>,
+        kind: LineKind,
+    ) -> Self {
+        let direction: [T; N] = to_exact(direction.into());
+        assert!(
+            direction.iter().any(|v| !v.is_zero()),
+            "Line direction must be non-zero"
+        );
+        Self {
+            origin: to_exact(origin.into()),
+            direction,
+            kind,
+        }
+    }
+
+    pub fn infinite<V: ToPrimitive>(
+        origin: impl Into<[V; N]>,
+        direction: impl Into<[V; N]>,
+    ) -> Self {
+        Self::new(origin, direction, LineKind::Line)
+    }
+
+    pub fn ray<V: ToPrimitive>(origin: impl Into<[V; N]>, direction: impl Into<[V; N]>) -> Self {
+        Self::new(origin, direction, LineKind::Ray)
+    }
+
+    pub fn segment<V: ToPrimitive>(start: impl Into<[V; N]>, end: impl Into<[V; N]>) -> Self {
+        let start = to_exact::<T, V, N>(start.into());
+        let end = to_exact::<T, V, N>(end.into());
+        let direction = array::from_fn(|d| end[d].clone() - start[d].clone());
+        assert!(
+            direction.iter().any(|v: &T| !v.is_zero()),
+            "Segment must have distinct ends"
+        );
+        Self {
+            origin: start,
+            direction,
+            kind: LineKind::Segment,
+        }
+    }
+
+    /// The point at parameter t, whether or not it's within the bounds of this line's kind.
+    pub fn at(&self, t: &Ratio<T>) -> [Ratio<T>; N] {
+        array::from_fn(|d| Ratio::from(self.origin[d].clone()) + t * self.direction[d].clone())
+    }
+
+    fn t_bounds(&self) -> (Option<Ratio<T>>, Option<Ratio<T>>) {
+        match self.kind {
+            LineKind::Line => (None, None),
+            LineKind::Ray => (Some(Ratio::zero()), None),
+            LineKind::Segment => (Some(Ratio::zero()), Some(Ratio::one())),
+        }
+    }
+
+    fn allows(&self, t: &Ratio<T>) -> bool {
+        let (lo, hi) = self.t_bounds();
+        !matches!(lo, Some(lo) if lo > *t) && !matches!(hi, Some(hi) if *t > hi)
+    }
+}
+
+impl<T: ExactValue> Line<T, 2> {
+    pub fn intersect(&self, other: &Self) -> IntersectionResult<T, 2> {
+        match intersect_3d(&self.to_3d(), &other.to_3d()) {
+            IntersectionResult::Point { point, t, s } => {
+                let [x, y, _] = point;
+                IntersectionResult::Point {
+                    point: [x, y],
+                    t,
+                    s,
+                }
+            }
+            IntersectionResult::Overlap { start, end } => {
+                IntersectionResult::Overlap { start, end }
+            }
+            IntersectionResult::None => IntersectionResult::None,
+        }
+    }
+
+    fn to_3d(&self) -> Line<T, 3> {
+        let [ox, oy] = self.origin.clone();
+        let [dx, dy] = self.direction.clone();
+        Line {
+            origin: [ox, oy, T::zero()],
+            direction: [dx, dy, T::zero()],
+            kind: self.kind,
+        }
+    }
+}
+
+impl<T: ExactValue> Line<T, 3> {
+    pub fn intersect(&self, other: &Self) -> IntersectionResult<T, 3> {
+        intersect_3d(self, other)
+    }
+}
+
+fn intersect_3d<T: ExactValue>(a: &Line<T, 3>, b: &Line<T, 3>) -> IntersectionResult<T, 3> {
+    // Solve a.origin + t * a.direction == b.origin + s * b.direction
+    let w = sub(&b.origin, &a.origin);
+    let normal = cross(&a.direction, &b.direction);
+    if is_zero(&normal) {
+        return intersect_parallel(a, b, &w);
+    }
+    if !dot(&w, &normal).is_zero() {
+        // Skew lines
+        return IntersectionResult::None;
+    }
+    let normal_sq = dot(&normal, &normal);
+    let t = Ratio::new(dot(&cross(&w, &b.direction), &normal), normal_sq.clone());
+    let s = Ratio::new(dot(&cross(&w, &a.direction), &normal), normal_sq);
+    if !a.allows(&t) || !b.allows(&s) {
+        return IntersectionResult::None;
+    }
+    IntersectionResult::Point {
+        point: a.at(&t),
+        t,
+        s,
+    }
+}
+
+fn intersect_parallel<T: ExactValue>(
+    a: &Line<T, 3>,
+    b: &Line<T, 3>,
+    w: &[T; 3],
+) -> IntersectionResult<T, 3> {
+    if !is_zero(&cross(w, &a.direction)) {
+        return IntersectionResult::None;
+    }
+    // Collinear, so map b's parameter range onto a's: b.at(s) == a.at(t0 + s * scale)
+    let a_len_sq = dot(&a.direction, &a.direction);
+    let t0 = Ratio::new(dot(w, &a.direction), a_len_sq.clone());
+    let scale = Ratio::new(dot(&b.direction, &a.direction), a_len_sq);
+    let to_t = |s: Ratio<T>| t0.clone() + s * scale.clone();
+    let (b_lo, b_hi) = b.t_bounds();
+    let (b_lo, b_hi) = if scale.is_positive() {
+        (b_lo.map(to_t), b_hi.map(to_t))
+    } else {
+        (b_hi.map(to_t), b_lo.map(to_t))
+    };
+    let (a_lo, a_hi) = a.t_bounds();
+    let start = bound(a_lo, b_lo, |x, y| x.max(y));
+    let end = bound(a_hi, b_hi, |x, y| x.min(y));
+    match (start, end) {
+        (Some(start), Some(end)) if start > end => IntersectionResult::None,
+        (Some(start), Some(end)) if start == end => {
+            let s = (start.clone() - t0) / scale;
+            IntersectionResult::Point {
+                point: a.at(&start),
+                t: start,
+                s,
+            }
+        }
+        (start, end) => IntersectionResult::Overlap { start, end },
+    }
+}
+
+fn bound<T: ExactValue>(
+    a: Option<Ratio<T>>,
+    b: Option<Ratio<T>>,
+    pick: impl Fn(Ratio<T>, Ratio<T>) -> Ratio<T>,
+) -> Option<Ratio<T>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(pick(a, b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn to_exact<T: ExactValue, V: ToPrimitive, const N: usize>(values: [V; N]) -> [T; N] {
+    values.map(|v| {
+        v.to_i128()
+            .and_then(T::from_i128)
+            .expect("Value doesn't fit in exact type")
+    })
+}
+
+fn sub<T: ExactValue>(a: &[T; 3], b: &[T; 3]) -> [T; 3] {
+    array::from_fn(|d| a[d].clone() - b[d].clone())
+}
+
+fn dot<T: ExactValue>(a: &[T; 3], b: &[T; 3]) -> T {
+    (0..3).fold(T::zero(), |acc, d| acc + a[d].clone() * b[d].clone())
+}
+
+fn cross<T: ExactValue>(a: &[T; 3], b: &[T; 3]) -> [T; 3] {
+    let [ax, ay, az] = a.clone();
+    let [bx, by, bz] = b.clone();
+    [
+        ay.clone() * bz.clone() - az.clone() * by.clone(),
+        az * bx.clone() - ax.clone() * bz,
+        ax * by - ay * bx,
+    ]
+}
+
+fn is_zero<T: ExactValue>(v: &[T; 3]) -> bool {
+    v.iter().all(|x| x.is_zero())
+}
+
+#[cfg(test)]
+mod test {
+    use num::rational::Ratio;
+    use num::BigInt;
+
+    use crate::line::{IntersectionResult, Line};
+    use crate::point2::{DeltaS, PointS};
+    use crate::point3::Point3;
+
+    fn r(n: i128, d: i128) -> Ratio<i128> {
+        Ratio::new(n, d)
+    }
+
+    #[test]
+    fn test_rays_2d() {
+        // Hailstones from the 2023 day 24 example
+        let a = Line::<i128, 2>::ray(PointS::new(19, 13), DeltaS::new(-2, 1));
+        let b = Line::<i128, 2>::ray(PointS::new(18, 19), DeltaS::new(-1, -1));
+        assert_eq!(
+            a.intersect(&b),
+            IntersectionResult::Point {
+                point: [r(43, 3), r(46, 3)],
+                t: r(7, 3),
+                s: r(11, 3),
+            }
+        );
+        // Crossed in the past for one of them
+        let c = Line::<i128, 2>::ray(PointS::new(20, 19), DeltaS::new(1, -5));
+        assert_eq!(a.intersect(&c), IntersectionResult::None);
+        // Parallel
+        let d = Line::<i128, 2>::ray(PointS::new(20, 25), DeltaS::new(-2, 1));
+        assert_eq!(a.intersect(&d), IntersectionResult::None);
+    }
+
+    #[test]
+    fn test_collinear_segments() {
+        let a = Line::<i128, 2>::segment([0, 0], [4, 0]);
+        let b = Line::<i128, 2>::segment([6, 0], [2, 0]);
+        assert_eq!(
+            a.intersect(&b),
+            IntersectionResult::Overlap {
+                start: Some(r(1, 2)),
+                end: Some(r(1, 1)),
+            }
+        );
+        let touching = Line::<i128, 2>::segment([4, 0], [8, 0]);
+        assert_eq!(
+            a.intersect(&touching),
+            IntersectionResult::Point {
+                point: [r(4, 1), r(0, 1)],
+                t: r(1, 1),
+                s: r(0, 1),
+            }
+        );
+        let apart = Line::<i128, 2>::segment([5, 0], [8, 0]);
+        assert_eq!(a.intersect(&apart), IntersectionResult::None);
+        let line = Line::<i128, 2>::infinite([-1, 0], [-1, 0]);
+        assert_eq!(
+            line.intersect(&a),
+            IntersectionResult::Overlap {
+                start: Some(r(-5, 1)),
+                end: Some(r(-1, 1)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_3d() {
+        let a = Line::<BigInt, 3>::infinite(Point3::new(0, 0, 0), Point3::new(1, 1, 1));
+        let b = Line::<BigInt, 3>::infinite(Point3::new(2, 0, 0), Point3::new(-1, 1, 1));
+        let IntersectionResult::Point { point, .. } = a.intersect(&b) else {
+            panic!("Expected a point");
+        };
+        assert_eq!(point, [1, 1, 1].map(|v| Ratio::from(BigInt::from(v))));
+        let skew = Line::<BigInt, 3>::infinite([0, 0, 5], [1, -1, 0]);
+        assert_eq!(a.intersect(&skew), IntersectionResult::None);
+    }
+}
@@ -0,0 +1,232 @@
+use itertools::Itertools;
+use num::{BigInt, BigRational, One, Zero};
+
+/// A dense matrix of exact rationals.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Matrix {
+    rows: Vec<Vec<BigRational>>,
+    num_cols: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum LinearSolution {
+    /// No assignment satisfies every equation
+    Inconsistent,
+    Unique(Vec<BigRational>),
+    /// Every solution is `particular` plus some combination of the `nullspace` vectors
+    Infinite {
+        particular: Vec<BigRational>,
+        nullspace: Vec<Vec<BigRational>>,
+    },
+}
+
+impl Matrix {
+    /// Panics if the rows have different lengths.
+    pub fn new(rows: Vec<Vec<BigRational>>) -> Self {
+        let num_cols = rows.first().map_or(0, |row| row.len());
+        assert!(
+            rows.iter().all(|row| row.len() == num_cols),
+            "All rows must be the same length"
+        );
+        Self { rows, num_cols }
+    }
+
+    pub fn from_integers<V: Into<BigInt> + Copy>(rows: &[Vec<V>]) -> Self {
+        Self::new(
+            rows.iter()
+                .map(|row| row.iter().map(|v| BigRational::from((*v).into())).collect())
+                .collect(),
+        )
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    pub fn rows(&self) -> &[Vec<BigRational>] {
+        &self.rows
+    }
+
+    /// The reduced row echelon form, along with the column of each row's pivot.
+    pub fn rref(&self) -> (Matrix, Vec<usize>) {
+        let mut rows = self.rows.clone();
+        let mut pivot_cols = Vec::new();
+        for col in 0..self.num_cols {
+            let pivot_row = pivot_cols.len();
+            let Some(found) = (pivot_row..rows.len()).find(|&r| !rows[r][col].is_zero()) else {
+                continue;
+            };
+            rows.swap(pivot_row, found);
+
+            let pivot = rows[pivot_row][col].clone();
+            for v in rows[pivot_row].iter_mut() {
+                *v /= &pivot;
+            }
+            let pivot_values = rows[pivot_row].clone();
+            for (r, row) in rows.iter_mut().enumerate() {
+                if r == pivot_row || row[col].is_zero() {
+                    continue;
+                }
+                let factor = row[col].clone();
+                for (v, p) in row.iter_mut().zip(&pivot_values).skip(col) {
+                    *v -= &factor * p;
+                }
+            }
+
+            pivot_cols.push(col);
+            if pivot_cols.len() == rows.len() {
+                break;
+            }
+        }
+        (
+            Matrix {
+                rows,
+                num_cols: self.num_cols,
+            },
+            pivot_cols,
+        )
+    }
+
+    pub fn rank(&self) -> usize {
+        self.rref().1.len()
+    }
+
+    /// A basis for the vectors x with self * x == 0.
+    pub fn nullspace(&self) -> Vec<Vec<BigRational>> {
+        let (reduced, pivot_cols) = self.rref();
+        nullspace_from_rref(&reduced, &pivot_cols, self.num_cols)
+    }
+
+    /// Solves self * x == rhs.
+    pub fn solve(&self, rhs: &[BigRational]) -> LinearSolution {
+        assert_eq!(rhs.len(), self.num_rows());
+        let augmented = Matrix::new(
+            self.rows
+                .iter()
+                .zip(rhs)
+                .map(|(row, b)| row.iter().chain([b]).cloned().collect())
+                .collect(),
+        );
+        let (reduced, pivot_cols) = augmented.rref();
+        if pivot_cols.last() == Some(&self.num_cols) {
+            // A pivot in the rhs column means 0 == non-zero
+            return LinearSolution::Inconsistent;
+        }
+
+        let mut particular = vec![BigRational::zero(); self.num_cols];
+        for (row, &col) in pivot_cols.iter().enumerate() {
+            particular[col] = reduced.rows[row][self.num_cols].clone();
+        }
+        if pivot_cols.len() == self.num_cols {
+            LinearSolution::Unique(particular)
+        } else {
+            LinearSolution::Infinite {
+                particular,
+                nullspace: nullspace_from_rref(&reduced, &pivot_cols, self.num_cols),
+            }
+        }
+    }
+
+    /// Solves self * x == rhs for integer inputs.
+    pub fn solve_integers<V: Into<BigInt> + Copy>(&self, rhs: &[V]) -> LinearSolution {
+        self.solve(
+            &rhs.iter()
+                .map(|v| BigRational::from((*v).into()))
+                .collect_vec(),
+        )
+    }
+}
+
+fn nullspace_from_rref(
+    reduced: &Matrix,
+    pivot_cols: &[usize],
+    num_cols: usize,
+) -> Vec<Vec<BigRational>> {
+    (0..num_cols)
+        .filter(|col| !pivot_cols.contains(col))
+        .map(|free_col| {
+            let mut vector = vec![BigRational::zero(); num_cols];
+            vector[free_col] = BigRational::one();
+            for (row, &pivot_col) in pivot_cols.iter().enumerate() {
+                vector[pivot_col] = -reduced.rows[row][free_col].clone();
+            }
+            vector
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use num::{BigInt, BigRational};
+
+    use crate::linear_algebra::{LinearSolution, Matrix};
+
+    fn ints(values: &[i64]) -> Vec<BigRational> {
+        values
+            .iter()
+            .map(|v| BigRational::from(BigInt::from(*v)))
+            .collect()
+    }
+
+    #[test]
+    fn test_rock_throw() {
+        // 2023 day 24 example. For hailstones i and j, the rock's position p and velocity v satisfy
+        // p × (vj - vi) - v × (pj - pi) = pj × vj - pi × vi
+        let hail = [
+            ([19, 13, 30], [-2, 1, -2]),
+            ([18, 19, 22], [-1, -1, -2]),
+            ([12, 31, 28], [-1, -2, -1]),
+        ];
+        let cross = |a: [i64; 3], b: [i64; 3]| {
+            [
+                a[1] * b[2] - a[2] * b[1],
+                a[2] * b[0] - a[0] * b[2],
+                a[0] * b[1] - a[1] * b[0],
+            ]
+        };
+        let mut rows = Vec::new();
+        let mut rhs = Vec::new();
+        for (pi, vi) in [hail[0]] {
+            for (pj, vj) in [hail[1], hail[2]] {
+                let d = [vj[0] - vi[0], vj[1] - vi[1], vj[2] - vi[2]];
+                let e = [pj[0] - pi[0], pj[1] - pi[1], pj[2] - pi[2]];
+                rows.push(vec![0, d[2], -d[1], 0, -e[2], e[1]]);
+                rows.push(vec![-d[2], 0, d[0], e[2], 0, -e[0]]);
+                rows.push(vec![d[1], -d[0], 0, -e[1], e[0], 0]);
+                let (cj, ci) = (cross(pj, vj), cross(pi, vi));
+                rhs.extend((0..3).map(|k| cj[k] - ci[k]));
+            }
+        }
+        let matrix = Matrix::from_integers(&rows);
+        assert_eq!(matrix.rank(), 6);
+        assert_eq!(
+            matrix.solve_integers(&rhs),
+            LinearSolution::Unique(ints(&[24, 13, 10, -3, 1, 2]))
+        );
+    }
+
+    #[test]
+    fn test_underdetermined_and_inconsistent() {
+        let matrix = Matrix::from_integers(&[vec![1, 2, 3], vec![2, 4, 6]]);
+        assert_eq!(matrix.rank(), 1);
+        assert_eq!(
+            matrix.nullspace(),
+            vec![ints(&[-2, 1, 0]), ints(&[-3, 0, 1])]
+        );
+        assert_eq!(
+            matrix.solve_integers(&[6, 12]),
+            LinearSolution::Infinite {
+                particular: ints(&[6, 0, 0]),
+                nullspace: vec![ints(&[-2, 1, 0]), ints(&[-3, 0, 1])],
+            }
+        );
+        assert_eq!(
+            matrix.solve_integers(&[6, 11]),
+            LinearSolution::Inconsistent
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A cache for recursive solvers. Results are stored under a key projected from the arguments,
+/// so anything that doesn't affect the answer, like a running total for logging, can be left out.
+pub struct Memo<A, K, V> {
+    key: fn(&A) -> K,
+    cache: HashMap<K, V>,
+    hits: usize,
+}
+
+impl<A, K: Hash + Eq, V: Clone> Memo<A, K, V> {
+    pub fn new(key: fn(&A) -> K) -> Self {
+        Self {
+            key,
+            cache: HashMap::new(),
+            hits: 0,
+        }
+    }
+
+    /// The cached result for these arguments, or else `solve(self, args)`, which gets the memo
+    /// back so it can make memoized recursive calls.
+    pub fn get(&mut self, args: A, solve: impl FnOnce(&mut Self, A) -> V) -> V {
+        let key = (self.key)(&args);
+        if let Some(known) = self.cache.get(&key) {
+            self.hits += 1;
+            return known.clone();
+        }
+        let result = solve(self, args);
+        self.cache.insert(key, result.clone());
+        result
+    }
+
+    /// Forgets every result, e.g. between puzzle inputs whose answers can't be shared.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.hits = 0;
+    }
+
+    /// The number of distinct results cached.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// How many lookups were answered from the cache since it was created or last cleared.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+}
+
+impl<A: Clone + Hash + Eq, V: Clone> Memo<A, A, V> {
+    /// Keyed by the whole arguments.
+    pub fn by_args() -> Self {
+        Self::new(A::clone)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::memo::Memo;
+
+    fn fib(memo: &mut Memo<u64, u64, u64>, n: u64) -> u64 {
+        memo.get(n, |memo, n| {
+            if n < 2 {
+                n
+            } else {
+                fib(memo, n - 1) + fib(memo, n - 2)
+            }
+        })
+    }
+
+    /// Ways to climb `remaining` steps, 1 or 2 at a time. The path taken so far doesn't change
+    /// the answer, so it's projected out of the key.
+    fn climbs(memo: &mut Memo<(u32, Vec<u32>), u32, u64>, remaining: u32, path: Vec<u32>) -> u64 {
+        memo.get(
+            (remaining, path),
+            |memo, (remaining, path)| match remaining {
+                0 => 1,
+                1 => climbs(memo, 0, [path, vec![1]].concat()),
+                _ => {
+                    climbs(memo, remaining - 1, [path.clone(), vec![1]].concat())
+                        + climbs(memo, remaining - 2, [path, vec![2]].concat())
+                }
+            },
+        )
+    }
+
+    #[test]
+    fn test_memo() {
+        let mut memo = Memo::by_args();
+        assert_eq!(fib(&mut memo, 90), 2880067194370816120);
+        assert_eq!(memo.len(), 91);
+        assert!(memo.hits() > 0);
+        memo.clear();
+        assert!(memo.is_empty());
+        assert_eq!(memo.hits(), 0);
+        assert_eq!(fib(&mut memo, 10), 55);
+
+        let mut memo = Memo::new(|(remaining, _)| *remaining);
+        assert_eq!(climbs(&mut memo, 40, vec![]), 165580141);
+        assert_eq!(memo.len(), 41);
+    }
+}
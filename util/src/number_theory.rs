@@ -0,0 +1,419 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use num::Integer;
+
+use crate::cycle_helpers::extended_gcd;
+
+/// (base ^ exp) % modulus, without overflowing for any u64 inputs.
+pub fn mod_pow(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    if modulus == 1 {
+        return 0;
+    }
+    let modulus = modulus as u128;
+    let mut base = base as u128 % modulus;
+    let mut result = 1u128;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+    result as u64
+}
+
+/// The x in 0..modulus with a * x ≡ 1 (mod modulus), if a and modulus are coprime.
+pub fn mod_inv(a: i128, modulus: i128) -> Option<i128> {
+    let (gcd, s, _t) = extended_gcd(a.rem_euclid(modulus), modulus);
+    (gcd == 1).then(|| s.rem_euclid(modulus))
+}
+
+/// The smallest x >= 0 with base ^ x ≡ target (mod modulus), using baby-step giant-step.
+///
+/// Base must be coprime with the modulus; returns None if there's no such x.
+pub fn discrete_log(base: u64, target: u64, modulus: u64) -> Option<u64> {
+    let target = target % modulus;
+    let steps = (modulus as f64).sqrt().ceil() as u64 + 1;
+
+    // Baby steps: remember the first exponent that reaches each value
+    let mut baby_steps = HashMap::new();
+    let mut current = 1 % modulus;
+    for j in 0..steps {
+        baby_steps.entry(current).or_insert(j);
+        current = mul_mod(current, base, modulus);
+    }
+
+    // Giant steps: multiply the target by base^-steps until it lands on a baby step
+    let giant_factor = mod_inv(mod_pow(base, steps, modulus) as i128, modulus as i128)? as u64;
+    let mut gamma = target;
+    for i in 0..steps {
+        if let Some(j) = baby_steps.get(&gamma) {
+            return Some(i * steps + j);
+        }
+        gamma = mul_mod(gamma, giant_factor, modulus);
+    }
+    None
+}
+
+fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    (a as u128 * b as u128 % modulus as u128) as u64
+}
+
+/// All primes <= max, via the sieve of Eratosthenes.
+pub fn primes_up_to(max: usize) -> Vec<usize> {
+    let mut is_prime = vec![true; max + 1];
+    let mut primes = Vec::new();
+    for n in 2..=max {
+        if !is_prime[n] {
+            continue;
+        }
+        primes.push(n);
+        for multiple in (n * n..=max).step_by(n) {
+            is_prime[multiple] = false;
+        }
+    }
+    primes
+}
+
+/// Prime factors of n with their multiplicities, in increasing order, by trial division.
+pub fn factorize(mut n: u64) -> Vec<(u64, u32)> {
+    let mut factors = Vec::new();
+    let mut p = 2;
+    while p <= n / p {
+        let mut count = 0;
+        while n % p == 0 {
+            n /= p;
+            count += 1;
+        }
+        if count > 0 {
+            factors.push((p, count));
+        }
+        p += if p == 2 { 1 } else { 2 };
+    }
+    if n > 1 {
+        factors.push((n, 1));
+    }
+    factors
+}
+
+/// All divisors of n, in increasing order.
+pub fn divisors(n: u64) -> Vec<u64> {
+    let mut result = vec![1];
+    for (p, count) in factorize(n) {
+        let existing = result.clone();
+        let mut power = 1;
+        for _ in 0..count {
+            power *= p;
+            result.extend(existing.iter().map(|d| d * power));
+        }
+    }
+    result.sort_unstable();
+    result
+}
+
+pub fn gcd_all<N: Integer + Copy>(values: impl IntoIterator<Item = N>) -> N {
+    values.into_iter().fold(N::zero(), |acc, v| acc.gcd(&v))
+}
+
+/// The least common multiple of all the values, or 1 if there are none.
+pub fn lcm_all<N: Integer + Copy>(values: impl IntoIterator<Item = N>) -> N {
+    values.into_iter().fold(N::one(), |acc, v| acc.lcm(&v))
+}
+
+/// (a + b) % modulus for values already reduced mod modulus, without overflowing even when the
+/// modulus is close to i128::MAX.
+fn add_mod_i128(a: i128, b: i128, modulus: i128) -> i128 {
+    if a >= modulus - b {
+        a - (modulus - b)
+    } else {
+        a + b
+    }
+}
+
+/// (a * b) % modulus, falling back to double-and-add when the product doesn't fit in an i128.
+/// Works for any positive modulus up to i128::MAX.
+fn mul_mod_i128(a: i128, b: i128, modulus: i128) -> i128 {
+    if let Some(product) = a.checked_mul(b) {
+        return product.rem_euclid(modulus);
+    }
+    let (mut a, mut b) = (a.rem_euclid(modulus), b.rem_euclid(modulus));
+    let mut result = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod_i128(result, a, modulus);
+        }
+        a = add_mod_i128(a, a, modulus);
+        b >>= 1;
+    }
+    result
+}
+
+/// The map x -> (a * x + b) mod n. These compose into another map of the same form, so a long
+/// sequence of linear steps (eg shuffling a deck) can be collapsed into one, then repeated a huge
+/// number of times by squaring.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct AffineMod {
+    pub a: i128,
+    pub b: i128,
+    pub modulus: i128,
+}
+
+impl AffineMod {
+    pub fn new(a: i128, b: i128, modulus: i128) -> Self {
+        assert!(modulus > 0, "Modulus must be positive, got {}", modulus);
+        Self {
+            a: a.rem_euclid(modulus),
+            b: b.rem_euclid(modulus),
+            modulus,
+        }
+    }
+
+    pub fn identity(modulus: i128) -> Self {
+        Self::new(1, 0, modulus)
+    }
+
+    pub fn apply(&self, x: i128) -> i128 {
+        let product = mul_mod_i128(self.a, x.rem_euclid(self.modulus), self.modulus);
+        add_mod_i128(product, self.b, self.modulus)
+    }
+
+    /// The map which applies self, then next.
+    pub fn then(&self, next: &Self) -> Self {
+        assert_eq!(self.modulus, next.modulus);
+        let modulus = self.modulus;
+        Self::new(
+            mul_mod_i128(next.a, self.a, modulus),
+            add_mod_i128(mul_mod_i128(next.a, self.b, modulus), next.b, modulus),
+            modulus,
+        )
+    }
+
+    /// The map that undoes this one, if a is coprime with the modulus.
+    pub fn inverse(&self) -> Option<Self> {
+        let a_inv = mod_inv(self.a, self.modulus)?;
+        Some(Self::new(
+            a_inv,
+            -mul_mod_i128(a_inv, self.b, self.modulus),
+            self.modulus,
+        ))
+    }
+
+    /// This map applied `times` times in a row. Negative counts apply the inverse instead.
+    pub fn pow(&self, times: i128) -> Option<Self> {
+        let mut base = if times < 0 { self.inverse()? } else { *self };
+        let mut times = times.unsigned_abs();
+        let mut result = Self::identity(self.modulus);
+        while times > 0 {
+            if times & 1 == 1 {
+                result = result.then(&base);
+            }
+            base = base.then(&base);
+            times >>= 1;
+        }
+        Some(result)
+    }
+}
+
+/// An integer modulo M. Division uses the modular inverse, so it panics if the divisor isn't
+/// coprime with M (which is never a problem when M is prime).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Default)]
+pub struct ModInt<const M: u64> {
+    value: u64,
+}
+
+impl<const M: u64> ModInt<M> {
+    pub fn new(value: i128) -> Self {
+        Self {
+            value: value.rem_euclid(M as i128) as u64,
+        }
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn pow(&self, exp: u64) -> Self {
+        Self {
+            value: mod_pow(self.value, exp, M),
+        }
+    }
+
+    pub fn inv(&self) -> Option<Self> {
+        mod_inv(self.value as i128, M as i128).map(Self::new)
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> Self {
+        Self { value: value % M }
+    }
+}
+
+impl<const M: u64> Display for ModInt<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.value as i128 + rhs.value as i128)
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.value as i128 - rhs.value as i128)
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            value: mul_mod(self.value, rhs.value, M),
+        }
+    }
+}
+
+impl<const M: u64> Div for ModInt<M> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.inv().expect("Divisor isn't invertible")
+    }
+}
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-(self.value as i128))
+    }
+}
+
+impl<const M: u64> AddAssign for ModInt<M> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const M: u64> SubAssign for ModInt<M> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const M: u64> MulAssign for ModInt<M> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const M: u64> DivAssign for ModInt<M> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::number_theory::{
+        discrete_log, divisors, factorize, lcm_all, mod_inv, mod_pow, mul_mod_i128, primes_up_to,
+        AffineMod, ModInt,
+    };
+
+    #[test]
+    fn test_mod_pow_and_inv() {
+        assert_eq!(mod_pow(7, 0, 13), 1);
+        assert_eq!(mod_pow(2, 62, u64::MAX), 1 << 62);
+        assert_eq!(mod_pow(3, 200, 1_000_000_007), 136_318_165);
+        assert_eq!(mod_inv(3, 7), Some(5));
+        assert_eq!(mod_inv(-3, 7), Some(2));
+        assert_eq!(mod_inv(4, 8), None);
+    }
+
+    #[test]
+    fn test_discrete_log() {
+        // 2020 day 25 example: 7 ^ 8 ≡ 5764801 (mod 20201227)
+        assert_eq!(discrete_log(7, 5764801, 20201227), Some(8));
+        assert_eq!(discrete_log(2, 1, 11), Some(0));
+        // 4 only generates {1, 4, 5, 9, 3} mod 11
+        assert_eq!(discrete_log(4, 2, 11), None);
+    }
+
+    #[test]
+    fn test_primes_and_factors() {
+        assert_eq!(primes_up_to(20), vec![2, 3, 5, 7, 11, 13, 17, 19]);
+        assert_eq!(factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factorize(1_000_000_007), vec![(1_000_000_007, 1)]);
+        assert_eq!(divisors(12), vec![1, 2, 3, 4, 6, 12]);
+        assert_eq!(lcm_all([4u64, 6, 10]), 60);
+    }
+
+    #[test]
+    fn test_mod_int() {
+        type M7 = ModInt<7>;
+        let a = M7::new(-1);
+        assert_eq!(a.value(), 6);
+        assert_eq!(a + M7::from(3), M7::from(2));
+        assert_eq!(M7::from(3) / M7::from(5) * M7::from(5), M7::from(3));
+        assert_eq!(-M7::from(0), M7::from(0));
+        assert_eq!(M7::from(3).pow(6), M7::from(1));
+    }
+
+    #[test]
+    fn test_affine_shuffle() {
+        // 2019 day 22 example: cut 6, deal with increment 7, deal into new stack
+        let deck_size = 10;
+        let shuffle = AffineMod::new(1, -6, deck_size)
+            .then(&AffineMod::new(7, 0, deck_size))
+            .then(&AffineMod::new(-1, -1, deck_size));
+        // Final deck is 3 0 7 4 1 8 5 2 9 6, so card 3 ends up at position 0
+        let positions = (0..deck_size)
+            .map(|card| shuffle.apply(card))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![1, 4, 7, 0, 3, 6, 9, 2, 5, 8]);
+        let card_at = shuffle.inverse().unwrap();
+        assert_eq!(card_at.apply(0), 3);
+
+        let many = shuffle.pow(1_000_000_000_007).unwrap();
+        let mut position = 2;
+        for _ in 0..(1_000_000_000_007i128 % 4) {
+            position = shuffle.apply(position);
+        }
+        // The shuffle has order 4 on this deck, since 7 ^ 4 ≡ 1 (mod 10) and the offsets cancel
+        assert_eq!(shuffle.pow(4).unwrap(), AffineMod::identity(deck_size));
+        assert_eq!(many.apply(2), position);
+        assert_eq!(shuffle.pow(-1), shuffle.inverse());
+    }
+
+    #[test]
+    fn test_huge_modulus() {
+        // 2^127 - 1, so products and sums of reduced values both overflow an i128
+        let m = i128::MAX;
+        assert_eq!(mul_mod_i128(m - 1, m - 1, m), 1);
+        assert_eq!(mul_mod_i128(m - 2, m - 3, m), 6);
+        assert_eq!(mul_mod_i128(1 << 126, 4, m), 2);
+        assert_eq!(mul_mod_i128(1 << 126, 1 << 126, m), 1 << 125);
+
+        // x -> -2x - 1, then x -> -3x + 5, is x -> 6x + 8
+        let first = AffineMod::new(-2, -1, m);
+        let second = AffineMod::new(-3, 5, m);
+        assert_eq!(first.apply(m - 3), 5);
+        assert_eq!(second.apply(m - 1), 8);
+        assert_eq!(first.then(&second), AffineMod::new(6, 8, m));
+        let inverse = first.inverse().unwrap();
+        assert_eq!(inverse.apply(first.apply(12345)), 12345);
+        assert_eq!(first.then(&inverse), AffineMod::identity(m));
+        assert_eq!(first.pow(2).unwrap(), AffineMod::new(4, 1, m));
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use num_traits::Zero;
+
+use crate::bitset::BitSet;
+use crate::graph::Graph;
+
+/// What a search accumulates over every complete path.
+trait PathValue<W>: Clone {
+    fn no_path() -> Self;
+    fn at_end() -> Self;
+    /// This value from the next node, plus the step to reach it.
+    fn extend(self, weight: &W) -> Self;
+    fn combine(self, other: Self) -> Self;
+}
+
+impl<W> PathValue<W> for u64 {
+    fn no_path() -> Self {
+        0
+    }
+
+    fn at_end() -> Self {
+        1
+    }
+
+    fn extend(self, _weight: &W) -> Self {
+        self
+    }
+
+    fn combine(self, other: Self) -> Self {
+        self + other
+    }
+}
+
+impl<W: Copy + Ord + Zero> PathValue<W> for Option<W> {
+    fn no_path() -> Self {
+        None
+    }
+
+    fn at_end() -> Self {
+        Some(W::zero())
+    }
+
+    fn extend(self, weight: &W) -> Self {
+        self.map(|length| length + *weight)
+    }
+
+    fn combine(self, other: Self) -> Self {
+        self.max(other)
+    }
+}
+
+struct Explorer<'a, N: Hash + Eq + Clone, W: Clone, X, R, F, const WORDS: usize> {
+    graph: &'a Graph<N, W>,
+    end: usize,
+    enter: F,
+    memo: Option<HashMap<(usize, BitSet<WORDS>, X), R>>,
+}
+
+impl<'a, N, W, X, R, F, const WORDS: usize> Explorer<'a, N, W, X, R, F, WORDS>
+where
+    N: Hash + Eq + Clone,
+    W: Clone,
+    X: Clone + Eq + Hash,
+    R: PathValue<W>,
+    F: Fn(usize, &BitSet<WORDS>, &X) -> Option<X>,
+{
+    fn run(
+        graph: &'a Graph<N, W>,
+        start: usize,
+        end: usize,
+        initial: X,
+        enter: F,
+        memoize: bool,
+    ) -> R {
+        assert!(
+            graph.len() <= BitSet::<WORDS>::CAPACITY,
+            "Graph has {} nodes but the visited set only holds {}",
+            graph.len(),
+            BitSet::<WORDS>::CAPACITY
+        );
+        let mut explorer = Self {
+            graph,
+            end,
+            enter,
+            memo: memoize.then(HashMap::new),
+        };
+        explorer.explore(start, BitSet::new().with(start), initial)
+    }
+
+    fn explore(&mut self, node: usize, visited: BitSet<WORDS>, extra: X) -> R {
+        if node == self.end {
+            return R::at_end();
+        }
+        let key = (node, visited, extra);
+        if let Some(known) = self.memo.as_ref().and_then(|memo| memo.get(&key)) {
+            return known.clone();
+        }
+        let graph = self.graph;
+        let mut result = R::no_path();
+        for (next, weight) in graph.edges(node) {
+            if let Some(next_extra) = (self.enter)(*next, &visited, &key.2) {
+                let from_next = self.explore(*next, visited.with(*next), next_extra);
+                result = result.combine(from_next.extend(weight));
+            }
+        }
+        if let Some(memo) = self.memo.as_mut() {
+            memo.insert(key, result.clone());
+        }
+        result
+    }
+}
+
+/// Counts every walk from start to end, which stops the first time it reaches end. The walk
+/// may step into `node` only if `enter(node, visited, extra)` returns the new extra state, where
+/// `visited` holds every node so far including the current one and `extra` is whatever else the
+/// rule tracks, like whether a revisit has been used up. With `memoize`, results are cached by
+/// (node, visited, extra), which pays off when many orders lead to the same set of nodes.
+pub fn count_paths<N, W, X, const WORDS: usize>(
+    graph: &Graph<N, W>,
+    start: usize,
+    end: usize,
+    initial: X,
+    enter: impl Fn(usize, &BitSet<WORDS>, &X) -> Option<X>,
+    memoize: bool,
+) -> u64
+where
+    N: Hash + Eq + Clone,
+    W: Clone,
+    X: Clone + Eq + Hash,
+{
+    Explorer::run(graph, start, end, initial, enter, memoize)
+}
+
+/// The heaviest walk from start to end under the same rules as `count_paths`, or None if end
+/// can't be reached.
+pub fn longest_path<N, W, X, const WORDS: usize>(
+    graph: &Graph<N, W>,
+    start: usize,
+    end: usize,
+    initial: X,
+    enter: impl Fn(usize, &BitSet<WORDS>, &X) -> Option<X>,
+    memoize: bool,
+) -> Option<W>
+where
+    N: Hash + Eq + Clone,
+    W: Copy + Ord + Zero,
+    X: Clone + Eq + Hash,
+{
+    Explorer::run(graph, start, end, initial, enter, memoize)
+}
+
+fn never_revisit<const WORDS: usize>(node: usize, visited: &BitSet<WORDS>, _: &()) -> Option<()> {
+    (!visited.contains(node)).then_some(())
+}
+
+/// The number of paths from start to end that visit each node at most once. Panics for graphs
+/// of more than 128 nodes.
+pub fn count_simple_paths<N: Hash + Eq + Clone, W: Clone>(
+    graph: &Graph<N, W>,
+    start: usize,
+    end: usize,
+) -> u64 {
+    if graph.len() <= 64 {
+        count_paths(graph, start, end, (), never_revisit::<1>, false)
+    } else {
+        count_paths(graph, start, end, (), never_revisit::<2>, false)
+    }
+}
+
+/// The heaviest path from start to end that visits each node at most once. Panics for graphs of
+/// more than 128 nodes.
+pub fn longest_simple_path<N: Hash + Eq + Clone, W: Copy + Ord + Zero>(
+    graph: &Graph<N, W>,
+    start: usize,
+    end: usize,
+) -> Option<W> {
+    if graph.len() <= 64 {
+        longest_path(graph, start, end, (), never_revisit::<1>, false)
+    } else {
+        longest_path(graph, start, end, (), never_revisit::<2>, false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bitset::BitSet64;
+    use crate::graph::Graph;
+    use crate::path_search::{count_paths, count_simple_paths, longest_simple_path};
+
+    #[test]
+    fn test_caves() {
+        // 2021 day 12 example
+        let mut caves: Graph<&str> = Graph::undirected();
+        for line in ["start-A", "start-b", "A-c", "A-b", "b-d", "A-end", "b-end"] {
+            let (a, b) = line.split_once('-').unwrap();
+            caves.add_edge(a, b);
+        }
+        let start = caves.index_of("start").unwrap();
+        let end = caves.index_of("end").unwrap();
+        let count = |allow_revisit: bool, memoize: bool| {
+            count_paths(
+                &caves,
+                start,
+                end,
+                false,
+                |node, visited: &BitSet64, revisited| {
+                    let small = caves.name(node).chars().all(|c| c.is_lowercase());
+                    if node == start {
+                        None
+                    } else if !small || !visited.contains(node) {
+                        Some(*revisited)
+                    } else if allow_revisit && !revisited {
+                        Some(true)
+                    } else {
+                        None
+                    }
+                },
+                memoize,
+            )
+        };
+        assert_eq!(count(false, false), 10);
+        assert_eq!(count(true, false), 36);
+        assert_eq!(count(true, true), 36);
+        assert_eq!(count_simple_paths(&caves, start, end), 4);
+    }
+
+    #[test]
+    fn test_longest() {
+        let mut graph: Graph<char, u32> = Graph::undirected();
+        for (a, b, w) in [
+            ('s', 'a', 1),
+            ('a', 'b', 5),
+            ('b', 'e', 1),
+            ('a', 'e', 10),
+            ('s', 'b', 1),
+        ] {
+            graph.add_weighted_edge(a, b, w);
+        }
+        let s = graph.index_of(&'s').unwrap();
+        let e = graph.index_of(&'e').unwrap();
+        // s -> b -> a -> e
+        assert_eq!(longest_simple_path(&graph, s, e), Some(16));
+
+        let mut directed: Graph<char, u32> = Graph::directed();
+        directed.add_weighted_edge('s', 'a', 1);
+        directed.add_weighted_edge('e', 'a', 1);
+        let (s, e) = (0, 2);
+        assert_eq!(longest_simple_path(&directed, s, e), None);
+    }
+}
@@ -1,6 +1,10 @@
+use std::ops::RangeInclusive;
+
+use itertools::Itertools;
 use num_traits::{Num, NumCast, Signed};
 
 use crate::additional_num_traits::{CheckedOps, NegOneConst, ZeroOneConst};
+use crate::box_set::{BoxSet, HyperBox};
 use crate::min_max;
 
 pub trait DeltaValue: Num + NumCast + ZeroOneConst + NegOneConst + Copy {}
@@ -87,6 +91,12 @@ impl<DV: DeltaValue> Delta<DV> {
     }
 }
 
+impl<DV: DeltaValue> From<Delta<DV>> for [DV; 2] {
+    fn from(delta: Delta<DV>) -> Self {
+        [delta.dx, delta.dy]
+    }
+}
+
 pub enum Rotation {
     Deg0,
     Deg90,
@@ -199,6 +209,29 @@ impl<PV: PointValue> Point<PV> {
             end: *other,
         }
     }
+
+    /// Rotates 45 degrees into (x + y, x - y). In the rotated space, L1 distance between the
+    /// original points is the Chebyshev (max per axis) distance, so diamonds become squares.
+    pub fn to_l1_rotated(&self) -> Point<i64> {
+        let x = num_traits::cast::<PV, i64>(self.x).unwrap();
+        let y = num_traits::cast::<PV, i64>(self.y).unwrap();
+        Point::new(x + y, x - y)
+    }
+
+    /// The inverse of `to_l1_rotated`. Returns None if the rotated point doesn't correspond to an
+    /// integer point (the coordinates have different parities), or doesn't fit in PV.
+    pub fn from_l1_rotated(rotated: &Point<i64>) -> Option<Self> {
+        if (rotated.x - rotated.y).rem_euclid(2) != 0 {
+            return None;
+        }
+        Point::new((rotated.x + rotated.y) / 2, (rotated.x - rotated.y) / 2).cast()
+    }
+}
+
+impl<PV: PointValue> From<Point<PV>> for [PV; 2] {
+    fn from(point: Point<PV>) -> Self {
+        [point.x, point.y]
+    }
 }
 
 enum Op {
@@ -268,6 +301,167 @@ impl_op_ex!(/ <PV: PointValue> |a: &Point<PV>, b: &PV| -> Point<PV> {
 impl_op_ex!(+= <PV: PointValue> |a: &mut Point<PV>, b: &Delta<PV::DeltaValueType>| { *a = *a + b });
 impl_op_ex!(-= <PV: PointValue> |a: &mut Point<PV>, b: &Delta<PV::DeltaValueType>| { *a = *a - b });
 
+/// All the points within an L1 (Manhattan) distance of the center, inclusive.
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub struct Diamond<PV: PointValue> {
+    pub center: Point<PV>,
+    pub radius: i64,
+}
+
+impl<PV: PointValue> Diamond<PV> {
+    pub fn new(center: Point<PV>, radius: i64) -> Self {
+        Self { center, radius }
+    }
+
+    /// The diamond centered on `center` that just reaches `edge`.
+    pub fn reaching(center: Point<PV>, edge: &Point<PV>) -> Self {
+        Self::new(center, l1_dist_i64(&center, edge))
+    }
+
+    pub fn contains(&self, point: &Point<PV>) -> bool {
+        l1_dist_i64(&self.center, point) <= self.radius
+    }
+
+    /// The diamond as a box in `to_l1_rotated` space, with an exclusive end. Not every point in
+    /// the box is the image of an integer point; see `from_l1_rotated`.
+    pub fn to_rotated_box(&self) -> HyperBox<i64, 2> {
+        let center = self.center.to_l1_rotated();
+        HyperBox::new(
+            [center.x - self.radius, center.y - self.radius],
+            [center.x + self.radius + 1, center.y + self.radius + 1],
+        )
+    }
+
+    /// The overlap of two diamonds, which is a rectangle in rotated space.
+    pub fn intersect(&self, other: &Self) -> Option<HyperBox<i64, 2>> {
+        self.to_rotated_box().intersect(&other.to_rotated_box())
+    }
+
+    /// The points in the diamond on row y, as an inclusive range of x values.
+    pub fn row_span(&self, y: i64) -> Option<RangeInclusive<i64>> {
+        let center = self.center.cast::<i64>().unwrap();
+        let half_width = self.radius - (center.y - y).abs();
+        (half_width >= 0).then(|| center.x - half_width..=center.x + half_width)
+    }
+
+    /// The points exactly `radius` away from the center, going clockwise from the top.
+    pub fn boundary(&self) -> impl Iterator<Item = Point<PV>> + '_ {
+        let center = self.center.cast::<i64>().unwrap();
+        let radius = self.radius;
+        let sides = if radius == 0 { 1 } else { 4 * radius };
+        (0..sides).filter_map(move |i| {
+            let (side, step) = (i / radius.max(1), i % radius.max(1));
+            let (dx, dy) = match side {
+                0 => (step, -radius + step),
+                1 => (radius - step, step),
+                2 => (-step, radius - step),
+                _ => (-radius + step, -step),
+            };
+            Point::new(center.x + dx, center.y + dy).cast()
+        })
+    }
+}
+
+fn l1_dist_i64<PV: PointValue>(a: &Point<PV>, b: &Point<PV>) -> i64 {
+    let a = a.cast::<i64>().unwrap();
+    let b = b.cast::<i64>().unwrap();
+    (a.x - b.x).abs() + (a.y - b.y).abs()
+}
+
+/// A union of diamonds, stored as disjoint boxes in `to_l1_rotated` space.
+#[derive(Debug, Clone, Default)]
+pub struct DiamondUnion {
+    boxes: BoxSet<i64, 2>,
+}
+
+impl DiamondUnion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<PV: PointValue>(&mut self, diamond: &Diamond<PV>) {
+        self.boxes.add(diamond.to_rotated_box());
+    }
+
+    pub fn remove<PV: PointValue>(&mut self, diamond: &Diamond<PV>) {
+        self.boxes.remove(diamond.to_rotated_box());
+    }
+
+    pub fn contains<PV: PointValue>(&self, point: &Point<PV>) -> bool {
+        self.boxes.contains(point.to_l1_rotated())
+    }
+
+    /// Number of integer points covered by at least one diamond.
+    pub fn num_points(&self) -> i64 {
+        self.boxes
+            .iter()
+            .map(|b| {
+                // Only rotated points where both coordinates have the same parity are real points
+                let (even_u, odd_u) = count_by_parity(b.start[0], b.end[0]);
+                let (even_v, odd_v) = count_by_parity(b.start[1], b.end[1]);
+                even_u * even_v + odd_u * odd_v
+            })
+            .sum()
+    }
+}
+
+/// Returns (evens, odds) in start..end
+fn count_by_parity(start: i64, end: i64) -> (i64, i64) {
+    let evens = (end + 1).div_euclid(2) - (start + 1).div_euclid(2);
+    (evens, end - start - evens)
+}
+
+/// The merged, sorted ranges of x covered by any of the diamonds on row y.
+pub fn diamond_row_coverage<'a, PV: PointValue>(
+    diamonds: impl IntoIterator<Item = &'a Diamond<PV>>,
+    y: i64,
+) -> Vec<RangeInclusive<i64>> {
+    let spans = diamonds
+        .into_iter()
+        .filter_map(|d| d.row_span(y))
+        .sorted_by_key(|span| *span.start());
+    let mut merged: Vec<RangeInclusive<i64>> = Vec::new();
+    for span in spans {
+        match merged.last_mut() {
+            Some(last) if *span.start() <= *last.end() + 1 => {
+                if span.end() > last.end() {
+                    *last = *last.start()..=*span.end();
+                }
+            }
+            _ => merged.push(span),
+        }
+    }
+    merged
+}
+
+/// Every point in the inclusive region from `min` to `max` which isn't in any of the diamonds.
+/// Scans row by row, jumping over covered spans, so it's fast as long as few points are uncovered.
+pub fn diamond_uncovered_points<PV: PointValue>(
+    diamonds: &[Diamond<PV>],
+    min: &Point<PV>,
+    max: &Point<PV>,
+) -> Vec<Point<PV>> {
+    let min = min.cast::<i64>().unwrap();
+    let max = max.cast::<i64>().unwrap();
+    let mut result = Vec::new();
+    for y in min.y..=max.y {
+        let mut x = min.x;
+        for span in diamond_row_coverage(diamonds, y) {
+            if *span.end() < x {
+                continue;
+            }
+            let gap_end = (*span.start() - 1).min(max.x);
+            result.extend((x..=gap_end).filter_map(|x| Point::new(x, y).cast()));
+            x = *span.end() + 1;
+            if x > max.x {
+                break;
+            }
+        }
+        result.extend((x..=max.x).filter_map(|x| Point::new(x, y).cast()));
+    }
+    result
+}
+
 /*
  * Impl all the traits!
  */
@@ -307,3 +501,170 @@ pub type DeltaS = Delta<i32>;
 pub type PointS = Point<i32>;
 pub type DeltaU = Delta<isize>;
 pub type PointU = Point<usize>;
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use crate::point2::{
+        count_by_parity, diamond_row_coverage, diamond_uncovered_points, Diamond, DiamondUnion,
+        Point, PointS, PointU,
+    };
+
+    #[test]
+    fn test_l1_rotation() {
+        for x in -4..=4 {
+            for y in -4..=4 {
+                let point = PointS::new(x, y);
+                assert_eq!(PointS::from_l1_rotated(&point.to_l1_rotated()), Some(point));
+            }
+        }
+        let point = PointU::new(7, 3);
+        assert_eq!(point.to_l1_rotated(), Point::new(10, 4));
+        assert_eq!(PointU::from_l1_rotated(&Point::new(10, 4)), Some(point));
+
+        // x + y and x - y always have the same parity
+        assert_eq!(PointS::from_l1_rotated(&Point::new(1, 0)), None);
+        assert_eq!(PointS::from_l1_rotated(&Point::new(-3, 2)), None);
+        // Valid parity, but not representable
+        assert_eq!(PointU::from_l1_rotated(&Point::new(0, 2)), None);
+    }
+
+    #[test]
+    fn test_count_by_parity() {
+        assert_eq!(count_by_parity(-3, 4), (3, 4));
+        assert_eq!(count_by_parity(0, 0), (0, 0));
+        assert_eq!(count_by_parity(1, 2), (0, 1));
+        assert_eq!(count_by_parity(-2, -1), (1, 0));
+    }
+
+    fn brute_force_count(diamonds: &[Diamond<i32>]) -> i64 {
+        let mut count = 0;
+        for x in -20..=20 {
+            for y in -20..=20 {
+                if diamonds.iter().any(|d| d.contains(&PointS::new(x, y))) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn test_diamond_union() {
+        let cases = [
+            // Overlapping, with centers of both parities
+            vec![
+                Diamond::new(PointS::new(0, 0), 3),
+                Diamond::new(PointS::new(3, 1), 2),
+            ],
+            // Nested
+            vec![
+                Diamond::new(PointS::new(1, 1), 5),
+                Diamond::new(PointS::new(2, 0), 1),
+            ],
+            // Sharing a single point
+            vec![
+                Diamond::new(PointS::new(0, 0), 2),
+                Diamond::new(PointS::new(4, 0), 2),
+            ],
+            // Adjacent, without sharing any points
+            vec![
+                Diamond::new(PointS::new(0, 0), 2),
+                Diamond::new(PointS::new(5, 0), 2),
+                Diamond::new(PointS::new(-2, 3), 0),
+            ],
+            // Diagonal neighbours, sharing an edge
+            vec![
+                Diamond::new(PointS::new(0, 0), 3),
+                Diamond::new(PointS::new(3, 3), 3),
+                Diamond::new(PointS::new(-6, 7), 4),
+            ],
+        ];
+        for diamonds in cases {
+            let mut union = DiamondUnion::new();
+            for diamond in &diamonds {
+                union.add(diamond);
+            }
+            assert_eq!(union.num_points(), brute_force_count(&diamonds));
+            for x in -10..=10 {
+                for y in -10..=10 {
+                    let point = PointS::new(x, y);
+                    assert_eq!(
+                        union.contains(&point),
+                        diamonds.iter().any(|d| d.contains(&point))
+                    );
+                }
+            }
+        }
+
+        let single = Diamond::new(PointS::new(0, 0), 4);
+        let mut union = DiamondUnion::new();
+        union.add(&single);
+        assert_eq!(union.num_points(), 41);
+        union.remove(&Diamond::new(PointS::new(0, 0), 1));
+        assert_eq!(union.num_points(), 36);
+        assert!(!union.contains(&PointS::new(1, 0)));
+        assert!(union.contains(&PointS::new(2, 0)));
+    }
+
+    #[test]
+    fn test_row_coverage() {
+        let diamonds = [
+            Diamond::new(PointS::new(0, 0), 2),
+            Diamond::new(PointS::new(5, 0), 2),
+            Diamond::new(PointS::new(0, 1), 1),
+            Diamond::new(PointS::new(20, 0), 1),
+            Diamond::new(PointS::new(10, 3), 2),
+        ];
+        // -2..=2 and 3..=7 touch, so they merge
+        assert_eq!(diamond_row_coverage(&diamonds, 0), vec![-2..=7, 19..=21]);
+        assert_eq!(
+            diamond_row_coverage(&diamonds, 2),
+            vec![0..=0, 5..=5, 9..=11]
+        );
+        assert_eq!(diamond_row_coverage(&diamonds, -5), vec![]);
+    }
+
+    #[test]
+    fn test_sensors() {
+        // 2022 day 15 example
+        let sensors = [
+            ((2, 18), (-2, 15)),
+            ((9, 16), (10, 16)),
+            ((13, 2), (15, 3)),
+            ((12, 14), (10, 16)),
+            ((10, 20), (10, 16)),
+            ((14, 17), (10, 16)),
+            ((8, 7), (2, 10)),
+            ((2, 0), (2, 10)),
+            ((0, 11), (2, 10)),
+            ((20, 14), (25, 17)),
+            ((17, 20), (21, 22)),
+            ((16, 7), (15, 3)),
+            ((14, 3), (15, 3)),
+            ((20, 1), (15, 3)),
+        ];
+        let diamonds = sensors
+            .iter()
+            .map(|((sx, sy), (bx, by))| {
+                Diamond::reaching(PointS::new(*sx, *sy), &PointS::new(*bx, *by))
+            })
+            .collect_vec();
+
+        let covered = diamond_row_coverage(&diamonds, 10);
+        assert_eq!(covered, vec![-2..=24]);
+        let beacons_on_row = sensors
+            .iter()
+            .filter(|(_, (_, by))| *by == 10)
+            .map(|(_, beacon)| beacon)
+            .unique()
+            .count() as i64;
+        let no_beacon = covered.iter().map(|r| r.end() - r.start() + 1).sum::<i64>();
+        assert_eq!(no_beacon - beacons_on_row, 26);
+
+        let uncovered =
+            diamond_uncovered_points(&diamonds, &PointS::new(0, 0), &PointS::new(20, 20));
+        assert_eq!(uncovered, vec![PointS::new(14, 11)]);
+    }
+}
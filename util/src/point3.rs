@@ -5,6 +5,7 @@ use itertools::Itertools;
 use num_traits::{Num, NumCast};
 
 use crate::additional_num_traits::{NegOneConst, ZeroOneConst};
+use crate::box_set::{BoxSet, HyperBox};
 use crate::min_max;
 
 pub trait PointValue:
@@ -46,6 +47,12 @@ impl<DV: PointValue> Delta3G<DV> {
     }
 }
 
+impl<DV: PointValue> From<Delta3G<DV>> for [DV; 3] {
+    fn from(delta: Delta3G<DV>) -> Self {
+        [delta.dx, delta.dy, delta.dz]
+    }
+}
+
 impl_op_ex!(+ <DV: PointValue> |a: &Delta3G<DV>, b: &Delta3G<DV>| -> Delta3G<DV> { Delta3G { dx: a.dx + b.dx, dy: a.dy + b.dy, dz: a.dz + b.dz }});
 impl_op_ex!(- <DV: PointValue> |a: &Delta3G<DV>, b: &Delta3G<DV>| -> Delta3G<DV> {
     Delta3G {
@@ -121,7 +128,7 @@ fn clamp<PV: PointValue>(val: PV, min: PV, max: PV) -> PV {
     }
 }
 
-fn max<PV: PointValue>(a: PV, b: PV) -> PV {
+pub(crate) fn max<PV: PointValue>(a: PV, b: PV) -> PV {
     if a > b {
         a
     } else {
@@ -129,7 +136,7 @@ fn max<PV: PointValue>(a: PV, b: PV) -> PV {
     }
 }
 
-fn min<PV: PointValue>(a: PV, b: PV) -> PV {
+pub(crate) fn min<PV: PointValue>(a: PV, b: PV) -> PV {
     if a < b {
         a
     } else {
@@ -137,6 +144,12 @@ fn min<PV: PointValue>(a: PV, b: PV) -> PV {
     }
 }
 
+impl<PV: PointValue> From<Point3G<PV>> for [PV; 3] {
+    fn from(point: Point3G<PV>) -> Self {
+        [point.x, point.y, point.z]
+    }
+}
+
 impl_op_ex!(+ <PV: PointValue> |a: &Point3G<PV>, b: &Delta3G<PV>| -> Point3G<PV> { Point3G { x: a.x + b.dx, y: a.y + b.dy, z: a.z + b.dz }});
 impl_op_ex!(- <PV: PointValue> |a: &Point3G<PV>, b: &Delta3G<PV>| -> Point3G<PV> {
     Point3G {
@@ -334,6 +347,20 @@ impl<PV: PointValue> BoundingBoxG<PV> {
     }
 }
 
+impl<PV: PointValue> From<BoundingBoxG<PV>> for HyperBox<PV, 3> {
+    fn from(bound: BoundingBoxG<PV>) -> Self {
+        HyperBox::new(bound.start.into(), bound.end.into())
+    }
+}
+
+impl<PV: PointValue> From<HyperBox<PV, 3>> for BoundingBoxG<PV> {
+    fn from(bound: HyperBox<PV, 3>) -> Self {
+        let [sx, sy, sz] = bound.start;
+        let [ex, ey, ez] = bound.end;
+        BoundingBoxG::new(Point3G::new(sx, sy, sz), Point3G::new(ex, ey, ez))
+    }
+}
+
 // Impl the Delta traits
 impl PointValue for isize {}
 impl PointValue for i8 {}
@@ -348,6 +375,8 @@ pub type Delta3 = Delta3G<i32>;
 pub type Point3 = Point3G<i32>;
 pub type BoundingBox = BoundingBoxG<i32>;
 pub type OverlapResult = OverlapResultG<i32>;
+pub type BoxSet3G<PV> = BoxSet<PV, 3>;
+pub type BoxSet3 = BoxSet3G<i32>;
 
 #[cfg(test)]
 mod test {
@@ -0,0 +1,153 @@
+use itertools::Itertools;
+use num::{BigInt, BigRational, ToPrimitive, Zero};
+
+/// A polynomial with exact rational coefficients, lowest degree first.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Polynomial {
+    coefficients: Vec<BigRational>,
+}
+
+impl Polynomial {
+    pub fn new(coefficients: Vec<BigRational>) -> Self {
+        let mut result = Self { coefficients };
+        result.trim();
+        result
+    }
+
+    /// The unique polynomial of degree < samples.len() passing through every (x, y) sample.
+    /// Panics if two samples share an x.
+    pub fn fit(samples: &[(i64, i64)]) -> Self {
+        assert!(
+            samples.iter().map(|(x, _)| x).all_unique(),
+            "Samples must have distinct x values"
+        );
+        let xs = samples.iter().map(|(x, _)| rational(*x)).collect_vec();
+
+        // Newton's divided differences, computed in place so that afterwards
+        // differences[i] = f[x_0, ..., x_i]
+        let mut differences = samples.iter().map(|(_, y)| rational(*y)).collect_vec();
+        for level in 1..samples.len() {
+            for i in (level..samples.len()).rev() {
+                differences[i] =
+                    (&differences[i] - &differences[i - 1]) / (&xs[i] - &xs[i - level]);
+            }
+        }
+
+        // Expand c_0 + (x - x_0)(c_1 + (x - x_1)(c_2 + ...)) from the inside out
+        let mut result = Self::new(vec![]);
+        for i in (0..samples.len()).rev() {
+            result = result.times_x_minus(&xs[i]);
+            result.add_constant(&differences[i]);
+        }
+        result
+    }
+
+    /// Fits a polynomial of at most the given degree to the first `degree + 1` samples, and
+    /// returns it only if the remaining samples are on it too.
+    pub fn fit_checked(samples: &[(i64, i64)], degree: usize) -> Option<Self> {
+        if samples.len() <= degree {
+            return None;
+        }
+        let result = Self::fit(&samples[..=degree]);
+        samples[degree + 1..]
+            .iter()
+            .all(|(x, y)| result.eval(*x) == rational(*y))
+            .then_some(result)
+    }
+
+    /// The lowest-degree polynomial passing through every sample.
+    pub fn fit_lowest_degree(samples: &[(i64, i64)]) -> Self {
+        (0..samples.len())
+            .find_map(|degree| Self::fit_checked(samples, degree))
+            .unwrap_or_else(|| Self::new(vec![]))
+    }
+
+    /// The degree, or None for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn coefficients(&self) -> &[BigRational] {
+        &self.coefficients
+    }
+
+    pub fn eval(&self, x: i64) -> BigRational {
+        let x = rational(x);
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(BigRational::zero(), |acc, c| acc * &x + c)
+    }
+
+    /// The value at x, if it's an integer that fits in an i128.
+    pub fn eval_i128(&self, x: i64) -> Option<i128> {
+        let value = self.eval(x);
+        value.is_integer().then(|| value.to_integer().to_i128())?
+    }
+
+    fn times_x_minus(&self, root: &BigRational) -> Self {
+        let mut coefficients = vec![BigRational::zero(); self.coefficients.len() + 1];
+        for (i, c) in self.coefficients.iter().enumerate() {
+            coefficients[i + 1] += c;
+            coefficients[i] -= c * root;
+        }
+        Self::new(coefficients)
+    }
+
+    fn add_constant(&mut self, constant: &BigRational) {
+        if self.coefficients.is_empty() {
+            self.coefficients.push(BigRational::zero());
+        }
+        self.coefficients[0] += constant;
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        while self.coefficients.last().is_some_and(|c| c.is_zero()) {
+            self.coefficients.pop();
+        }
+    }
+}
+
+fn rational(value: i64) -> BigRational {
+    BigRational::from(BigInt::from(value))
+}
+
+/// Treats the values as samples at x = 0, 1, 2, ... and evaluates the lowest-degree polynomial
+/// through them at `index`, which may be past either end. Returns None if the result isn't an
+/// integer or doesn't fit in an i128.
+pub fn extrapolate_sequence(values: &[i64], index: i64) -> Option<i128> {
+    let samples = values
+        .iter()
+        .enumerate()
+        .map(|(x, y)| (x as i64, *y))
+        .collect_vec();
+    Polynomial::fit_lowest_degree(&samples).eval_i128(index)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::polynomial::{extrapolate_sequence, Polynomial};
+
+    #[test]
+    fn test_extrapolate_sequence() {
+        // 2023 day 9 examples
+        assert_eq!(extrapolate_sequence(&[0, 3, 6, 9, 12, 15], 6), Some(18));
+        assert_eq!(extrapolate_sequence(&[1, 3, 6, 10, 15, 21], 6), Some(28));
+        assert_eq!(extrapolate_sequence(&[10, 13, 16, 21, 30, 45], 6), Some(68));
+        assert_eq!(extrapolate_sequence(&[10, 13, 16, 21, 30, 45], -1), Some(5));
+    }
+
+    #[test]
+    fn test_fit() {
+        // y = x^2 / 2 - 3, sampled at uneven points
+        let samples = [(-4, 5), (2, -1), (6, 15), (10, 47)];
+        assert_eq!(Polynomial::fit_checked(&samples, 1), None);
+        let quadratic = Polynomial::fit_checked(&samples, 2).unwrap();
+        assert_eq!(quadratic.degree(), Some(2));
+        assert_eq!(quadratic.eval_i128(1_000_000), Some(499_999_999_997));
+        assert_eq!(quadratic.eval_i128(1), None);
+        assert_eq!(Polynomial::fit(&samples), quadratic);
+        assert_eq!(Polynomial::fit(&[(3, 0), (5, 0)]).degree(), None);
+    }
+}
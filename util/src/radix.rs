@@ -0,0 +1,335 @@
+use std::error::Error;
+
+use itertools::Itertools;
+
+const STANDARD_ALPHABET: &str = "0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// A positional number system. The base may be negative, and the digits are the |base|
+/// consecutive values starting at `min_digit`, written with the matching characters of the
+/// alphabet.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Radix {
+    base: i64,
+    min_digit: i64,
+    alphabet: Vec<char>,
+}
+
+impl Radix {
+    /// The digits must include zero, and the alphabet must have |base| distinct characters.
+    pub fn new(base: i64, min_digit: i64, alphabet: &str) -> Result<Self, Box<dyn Error>> {
+        let alphabet = alphabet.chars().collect_vec();
+        if base.abs() < 2 {
+            return Err(format!("Base must be at least 2 in magnitude, got {}", base).into());
+        }
+        if alphabet.len() as i64 != base.abs() {
+            return Err(format!(
+                "Base {} needs {} digits but the alphabet has {}",
+                base,
+                base.abs(),
+                alphabet.len()
+            )
+            .into());
+        }
+        if !alphabet.iter().all_unique() {
+            return Err("Alphabet has repeated characters".into());
+        }
+        if min_digit > 0 || min_digit + base.abs() <= 0 {
+            return Err(format!("Digits starting at {} don't include zero", min_digit).into());
+        }
+        Ok(Self {
+            base,
+            min_digit,
+            alphabet,
+        })
+    }
+
+    /// Base 2 to 36 using 0-9 then a-z. Parsing also accepts uppercase.
+    pub fn standard(base: u32) -> Self {
+        assert!((2..=36).contains(&base), "Standard bases are 2 to 36");
+        Self::new(base as i64, 0, &STANDARD_ALPHABET[..base as usize]).unwrap()
+    }
+
+    /// Base -2 to -36, where every integer is written without a sign.
+    pub fn negative(base: u32) -> Self {
+        assert!((2..=36).contains(&base), "Negative bases are -2 to -36");
+        Self::new(-(base as i64), 0, &STANDARD_ALPHABET[..base as usize]).unwrap()
+    }
+
+    /// A balanced base with digits -(n / 2)..=(n / 2), where n is the odd alphabet length and
+    /// the alphabet lists the digits from most negative to most positive.
+    pub fn balanced(alphabet: &str) -> Result<Self, Box<dyn Error>> {
+        let base = alphabet.chars().count() as i64;
+        if base % 2 == 0 {
+            return Err("Balanced bases need an odd number of digits".into());
+        }
+        Self::new(base, -(base / 2), alphabet)
+    }
+
+    /// Balanced base 5 written with "=-012", as in the 2022 day 25 puzzle.
+    pub fn snafu() -> Self {
+        Self::balanced("=-012").unwrap()
+    }
+
+    pub fn base(&self) -> i64 {
+        self.base
+    }
+
+    /// Whether negative numbers need a leading '-', because no digit string can represent them.
+    fn needs_sign(&self, n: i128) -> bool {
+        let max_digit = self.min_digit + self.base.abs() - 1;
+        self.base > 0 && ((n < 0 && self.min_digit == 0) || (n > 0 && max_digit == 0))
+    }
+
+    /// The digit values of n, most significant first. Zero is a single 0 digit. Panics if n can
+    /// only be written with a sign.
+    pub fn to_digits(&self, n: i128) -> Vec<i64> {
+        assert!(!self.needs_sign(n), "{} has no unsigned representation", n);
+        self.digits_from(n, self.min_digit)
+    }
+
+    /// Like `to_digits`, with the digits starting at `min_digit` instead. Steps through n
+    /// without ever leaving the range of an i128, so it works all the way out to the extremes.
+    fn digits_from(&self, mut n: i128, min_digit: i64) -> Vec<i64> {
+        let base = self.base as i128;
+        let min_digit = min_digit as i128;
+        let mut digits = Vec::new();
+        loop {
+            let digit = (n.rem_euclid(base.abs()) - min_digit).rem_euclid(base.abs()) + min_digit;
+            digits.push(digit as i64);
+            // (n - digit) / base, where n % base and digit only differ by a multiple of base
+            n = n / base + (n % base - digit) / base;
+            if n == 0 {
+                break;
+            }
+        }
+        digits.reverse();
+        digits
+    }
+
+    /// The value of the digits, most significant first, or None on overflow.
+    pub fn from_digits(&self, digits: &[i64]) -> Option<i128> {
+        let base = self.base as i128;
+        digits.iter().try_fold(0i128, |acc, digit| {
+            let digit = *digit as i128;
+            acc.checked_mul(base)
+                .and_then(|shifted| shifted.checked_add(digit))
+                .or_else(|| {
+                    // With negative digits, the last step can go out of range before the digit
+                    // brings it back, so move one base's worth from the shift to the digit
+                    let nudge = acc.signum();
+                    (acc - nudge)
+                        .checked_mul(base)?
+                        .checked_add(digit + nudge * base)
+                })
+        })
+    }
+
+    /// Writes n in this base, with a leading '-' only if there's no other way to write it.
+    pub fn format(&self, n: i128) -> String {
+        if self.needs_sign(n) {
+            // The digits of -n are the negated digits of n, using the negated digit range. That
+            // avoids negating n itself, which overflows for i128::MIN.
+            let max_digit = self.min_digit + self.base.abs() - 1;
+            let digits = self.digits_from(n, -max_digit);
+            return format!("-{}", self.write(digits.into_iter().map(|d| -d)));
+        }
+        self.write(self.to_digits(n).into_iter())
+    }
+
+    fn write(&self, digits: impl Iterator<Item = i64>) -> String {
+        digits
+            .map(|digit| self.alphabet[(digit - self.min_digit) as usize])
+            .collect()
+    }
+
+    pub fn parse(&self, s: &str) -> Result<i128, Box<dyn Error>> {
+        if let Some(rest) = s.strip_prefix('-') {
+            if !self.alphabet.contains(&'-') {
+                // Negate digit by digit so i128::MIN doesn't overflow on the way
+                let digits = self.parse_digits(rest)?.iter().map(|d| -d).collect_vec();
+                return self
+                    .from_digits(&digits)
+                    .ok_or_else(|| format!("{} overflowed", s).into());
+            }
+        }
+        self.from_digits(&self.parse_digits(s)?)
+            .ok_or_else(|| format!("{} overflowed", s).into())
+    }
+
+    fn parse_digits(&self, s: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+        if s.is_empty() {
+            return Err("Can't parse an empty string".into());
+        }
+        s.chars()
+            .map(|c| {
+                self.alphabet
+                    .iter()
+                    .position(|d| *d == c)
+                    .or_else(|| {
+                        self.alphabet
+                            .iter()
+                            .position(|d| *d == c.to_ascii_lowercase())
+                    })
+                    .map(|idx| idx as i64 + self.min_digit)
+                    .ok_or_else(|| format!("Invalid digit '{}' in {}", c, s).into())
+            })
+            .collect()
+    }
+}
+
+/// Expands hex digits (either case) into bits, most significant first.
+pub fn hex_to_bits(s: &str) -> Result<Vec<bool>, Box<dyn Error>> {
+    let mut bits = Vec::with_capacity(s.len() * 4);
+    for c in s.chars() {
+        let nibble = c
+            .to_digit(16)
+            .ok_or_else(|| format!("Invalid hex digit '{}'", c))?;
+        bits.extend((0..4).rev().map(|shift| nibble >> shift & 1 == 1));
+    }
+    Ok(bits)
+}
+
+/// Reads big-endian unsigned values of any width from a sequence of bits.
+#[derive(Debug, Clone)]
+pub struct BitStream {
+    bits: Vec<bool>,
+    position: usize,
+}
+
+impl BitStream {
+    pub fn new(bits: Vec<bool>) -> Self {
+        Self { bits, position: 0 }
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::new(hex_to_bits(s)?))
+    }
+
+    /// How many bits have been read so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bits.len() - self.position
+    }
+
+    pub fn read_bool(&mut self) -> Option<bool> {
+        let bit = *self.bits.get(self.position)?;
+        self.position += 1;
+        Some(bit)
+    }
+
+    /// Reads the next num_bits (at most 64) as a number, or returns None without consuming
+    /// anything if there aren't enough left.
+    pub fn read(&mut self, num_bits: usize) -> Option<u64> {
+        assert!(num_bits <= 64, "Can read at most 64 bits at once");
+        if num_bits > self.remaining() {
+            return None;
+        }
+        let value = self.bits[self.position..self.position + num_bits]
+            .iter()
+            .fold(0, |acc, bit| acc << 1 | u64::from(*bit));
+        self.position += num_bits;
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::radix::{BitStream, Radix};
+
+    #[test]
+    fn test_snafu() {
+        let snafu = Radix::snafu();
+        for (decimal, written) in [
+            (1, "1"),
+            (3, "1="),
+            (8, "2="),
+            (2022, "1=11-2"),
+            (314159265, "1121-1110-1=0"),
+            (4890, "2=-1=0"),
+        ] {
+            assert_eq!(snafu.format(decimal), written);
+            assert_eq!(snafu.parse(written).unwrap(), decimal);
+        }
+        assert_eq!(snafu.format(-3), "-2");
+        assert!(snafu.parse("12a").is_err());
+    }
+
+    #[test]
+    fn test_other_bases() {
+        let hex = Radix::standard(16);
+        assert_eq!(hex.format(-255), "-ff");
+        assert_eq!(hex.parse("FF").unwrap(), 255);
+        assert_eq!(hex.parse("-7f").unwrap(), -127);
+        assert_eq!(Radix::standard(2).format(0), "0");
+
+        let negabinary = Radix::negative(2);
+        assert_eq!(negabinary.format(-3), "1101");
+        assert_eq!(negabinary.format(6), "11010");
+        assert_eq!(negabinary.parse("1101").unwrap(), -3);
+
+        let ternary = Radix::balanced("T01").unwrap();
+        assert_eq!(ternary.to_digits(-5), vec![-1, 1, 1]);
+        assert_eq!(ternary.format(5), "1TT");
+
+        assert!(Radix::new(3, 1, "123").is_err());
+        assert!(Radix::balanced("0123").is_err());
+        assert!(Radix::standard(10).parse("1".repeat(50).as_str()).is_err());
+    }
+
+    #[test]
+    fn test_extremes() {
+        let decimal = Radix::standard(10);
+        assert_eq!(
+            decimal.format(i128::MIN),
+            "-170141183460469231731687303715884105728"
+        );
+        assert_eq!(
+            Radix::standard(16).format(i128::MIN),
+            format!("-8{}", "0".repeat(31))
+        );
+        let radixes = [
+            decimal,
+            Radix::standard(2),
+            Radix::standard(36),
+            Radix::negative(2),
+            Radix::snafu(),
+            Radix::balanced("T01").unwrap(),
+        ];
+        for radix in radixes {
+            for n in [i128::MIN, i128::MIN + 1, i128::MAX, i128::MAX - 1] {
+                let written = radix.format(n);
+                assert_eq!(radix.parse(&written).unwrap(), n, "{:?} {}", radix, written);
+            }
+        }
+        assert!(Radix::standard(10)
+            .parse("170141183460469231731687303715884105728")
+            .is_err());
+        assert!(Radix::snafu()
+            .parse(&format!("2{}", "2".repeat(55)))
+            .is_err());
+        // Parsing this one back would overflow partway, but writing it is fine
+        let digits = Radix::negative(3).to_digits(i128::MIN);
+        assert!(digits.iter().all(|digit| (0..3).contains(digit)));
+        assert_eq!(digits.len(), 82);
+    }
+
+    #[test]
+    fn test_bit_stream() {
+        // 2021 day 16 literal packet: version 6, type 4, value 2021
+        let mut stream = BitStream::from_hex("D2FE28").unwrap();
+        assert_eq!(stream.read(3), Some(6));
+        assert_eq!(stream.read(3), Some(4));
+        let mut value = 0;
+        while stream.read_bool() == Some(true) {
+            value = value << 4 | stream.read(4).unwrap();
+        }
+        value = value << 4 | stream.read(4).unwrap();
+        assert_eq!(value, 2021);
+        assert_eq!(stream.position(), 21);
+        assert_eq!(stream.read(4), None);
+        assert_eq!(stream.remaining(), 3);
+    }
+}
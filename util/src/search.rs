@@ -0,0 +1,567 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use num_traits::Zero;
+
+use crate::bitset::BitSet;
+
+/// Path costs. Must never be negative.
+pub trait SearchCost: Copy + Ord + Zero + Debug {}
+
+impl<T: Copy + Ord + Zero + Debug> SearchCost for T {}
+
+/// A node in a weighted search. Anything the state needs that isn't part of its identity, like
+/// the grid being searched, goes in the `Context` so it isn't hashed or cloned.
+pub trait SearchState: Clone + Eq + Hash {
+    type Cost: SearchCost;
+    type Context: ?Sized;
+
+    /// The states reachable in one step, each with the cost of that step.
+    fn successors(&self, context: &Self::Context) -> Vec<(Self, Self::Cost)>;
+
+    fn is_goal(&self, context: &Self::Context) -> bool;
+
+    /// A lower bound on the remaining cost to a goal, which turns the search into A*. It must
+    /// never overestimate, and shouldn't drop by more than a step's cost along any step. The
+    /// default of zero gives Dijkstra's algorithm.
+    fn heuristic(&self, _context: &Self::Context) -> Self::Cost {
+        Self::Cost::zero()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SearchResult<S: SearchState> {
+    pub cost: S::Cost,
+    /// From the start state to the goal state, inclusive.
+    pub path: Vec<S>,
+    /// How many states were expanded before reaching the goal.
+    pub visited: usize,
+}
+
+/// Every cheapest path from the starts to the goals, stored as predecessor links so they can be
+/// enumerated or just unioned without materializing each one.
+pub struct OptimalPaths<S: SearchState> {
+    pub cost: S::Cost,
+    /// How many states were expanded while looking for all optimal paths.
+    pub visited: usize,
+    nodes: Vec<Node<S>>,
+    goals: Vec<usize>,
+}
+
+impl<S: SearchState> OptimalPaths<S> {
+    /// Every goal state reachable at the optimal cost.
+    pub fn goals(&self) -> impl Iterator<Item = &S> {
+        self.goals.iter().map(|idx| &self.nodes[*idx].state)
+    }
+
+    /// One of the optimal paths, from start to goal inclusive.
+    pub fn path(&self) -> Vec<S> {
+        path_to(&self.nodes, self.goals[0])
+    }
+
+    /// Every optimal path. There can be exponentially many, so prefer `states` or `cells` when
+    /// only the union is needed.
+    pub fn paths(&self) -> Vec<Vec<S>> {
+        let mut result = Vec::new();
+        for goal in &self.goals {
+            self.extend_paths(*goal, &mut vec![], &mut result);
+        }
+        result
+    }
+
+    /// Every state that's on at least one optimal path.
+    pub fn states(&self) -> HashSet<&S> {
+        self.on_optimal_paths()
+            .into_iter()
+            .map(|idx| &self.nodes[idx].state)
+            .collect()
+    }
+
+    /// The distinct projections of every state on at least one optimal path, e.g. just the
+    /// position when the state also tracks a direction.
+    pub fn cells<P: Hash + Eq>(&self, project: impl Fn(&S) -> P) -> HashSet<P> {
+        self.on_optimal_paths()
+            .into_iter()
+            .map(|idx| project(&self.nodes[idx].state))
+            .collect()
+    }
+
+    fn on_optimal_paths(&self) -> HashSet<usize> {
+        let mut seen: HashSet<usize> = self.goals.iter().copied().collect();
+        let mut to_visit = self.goals.clone();
+        while let Some(idx) = to_visit.pop() {
+            for parent in &self.nodes[idx].parents {
+                if seen.insert(*parent) {
+                    to_visit.push(*parent);
+                }
+            }
+        }
+        seen
+    }
+
+    fn extend_paths(&self, idx: usize, suffix: &mut Vec<usize>, result: &mut Vec<Vec<S>>) {
+        suffix.push(idx);
+        let parents = &self.nodes[idx].parents;
+        if parents.is_empty() {
+            result.push(
+                suffix
+                    .iter()
+                    .rev()
+                    .map(|idx| self.nodes[*idx].state.clone())
+                    .collect(),
+            );
+        }
+        for parent in parents {
+            self.extend_paths(*parent, suffix, result);
+        }
+        suffix.pop();
+    }
+}
+
+struct Node<S: SearchState> {
+    state: S,
+    cost: S::Cost,
+    /// Every predecessor that reaches this state at its current cost, best found first.
+    parents: Vec<usize>,
+    expanded: bool,
+}
+
+fn path_to<S: SearchState>(nodes: &[Node<S>], idx: usize) -> Vec<S> {
+    let mut path = vec![nodes[idx].state.clone()];
+    let mut current = idx;
+    while let Some(parent) = nodes[current].parents.first() {
+        path.push(nodes[*parent].state.clone());
+        current = *parent;
+    }
+    path.reverse();
+    path
+}
+
+/// (estimated total cost, cost so far, node index), reversed so the cheapest estimate pops first.
+type HeapEntry<C> = (Reverse<C>, Reverse<C>, usize);
+
+/// The shared best-first loop, ordered by cost plus heuristic.
+struct Frontier<'a, S: SearchState> {
+    context: &'a S::Context,
+    nodes: Vec<Node<S>>,
+    indices: HashMap<S, usize>,
+    heap: BinaryHeap<HeapEntry<S::Cost>>,
+    visited: usize,
+}
+
+impl<'a, S: SearchState> Frontier<'a, S> {
+    fn new(starts: impl IntoIterator<Item = S>, context: &'a S::Context) -> Self {
+        let mut frontier = Self {
+            context,
+            nodes: Vec::new(),
+            indices: HashMap::new(),
+            heap: BinaryHeap::new(),
+            visited: 0,
+        };
+        for start in starts {
+            frontier.relax(start, S::Cost::zero(), None);
+        }
+        frontier
+    }
+
+    fn relax(&mut self, state: S, cost: S::Cost, parent: Option<usize>) {
+        let idx = match self.indices.entry(state) {
+            Entry::Occupied(entry) => {
+                let node = &mut self.nodes[*entry.get()];
+                if cost == node.cost {
+                    node.parents.extend(parent);
+                }
+                if node.expanded || cost >= node.cost {
+                    return;
+                }
+                node.cost = cost;
+                node.parents = parent.into_iter().collect();
+                *entry.get()
+            }
+            Entry::Vacant(entry) => {
+                self.nodes.push(Node {
+                    state: entry.key().clone(),
+                    cost,
+                    parents: parent.into_iter().collect(),
+                    expanded: false,
+                });
+                *entry.insert(self.nodes.len() - 1)
+            }
+        };
+        let estimate = cost + self.nodes[idx].state.heuristic(self.context);
+        self.heap.push((Reverse(estimate), Reverse(cost), idx));
+    }
+
+    /// The next unexpanded node as (estimated total cost, node index).
+    fn pop(&mut self) -> Option<(S::Cost, usize)> {
+        while let Some((Reverse(estimate), Reverse(cost), idx)) = self.heap.pop() {
+            if !self.nodes[idx].expanded && self.nodes[idx].cost == cost {
+                return Some((estimate, idx));
+            }
+        }
+        None
+    }
+
+    fn is_goal(&self, idx: usize) -> bool {
+        self.nodes[idx].state.is_goal(self.context)
+    }
+
+    fn expand(&mut self, idx: usize) {
+        self.nodes[idx].expanded = true;
+        self.visited += 1;
+        let cost = self.nodes[idx].cost;
+        for (next, step_cost) in self.nodes[idx].state.successors(self.context) {
+            self.relax(next, cost + step_cost, Some(idx));
+        }
+    }
+}
+
+/// Finds the cheapest path from any of the starts to a goal state, or None if no goal is
+/// reachable.
+pub fn search<S: SearchState>(
+    starts: impl IntoIterator<Item = S>,
+    context: &S::Context,
+) -> Option<SearchResult<S>> {
+    let mut frontier = Frontier::new(starts, context);
+    while let Some((_, idx)) = frontier.pop() {
+        if frontier.is_goal(idx) {
+            return Some(SearchResult {
+                cost: frontier.nodes[idx].cost,
+                path: path_to(&frontier.nodes, idx),
+                visited: frontier.visited,
+            });
+        }
+        frontier.expand(idx);
+    }
+    None
+}
+
+/// Like `search`, but keeps going until every path as cheap as the best one has been found.
+/// Goal states aren't expanded, so no optimal path passes through one goal to reach another.
+/// Steps should have positive costs, since zero-cost cycles would make every state in them a
+/// predecessor of the others.
+pub fn search_all_optimal<S: SearchState>(
+    starts: impl IntoIterator<Item = S>,
+    context: &S::Context,
+) -> Option<OptimalPaths<S>> {
+    let mut frontier = Frontier::new(starts, context);
+    let mut best_cost = None;
+    let mut goals = Vec::new();
+    while let Some((estimate, idx)) = frontier.pop() {
+        if best_cost.is_some_and(|best| estimate > best) {
+            break;
+        }
+        if frontier.is_goal(idx) {
+            best_cost = Some(frontier.nodes[idx].cost);
+            goals.push(idx);
+            frontier.nodes[idx].expanded = true;
+        } else {
+            frontier.expand(idx);
+        }
+    }
+    Some(OptimalPaths {
+        cost: best_cost?,
+        visited: frontier.visited,
+        nodes: frontier.nodes,
+        goals,
+    })
+}
+
+/// A world shared by several interchangeable agents, where each step moves one of them. Agents
+/// that are equal are treated as the same agent, so only their positions need to be distinct.
+pub trait SharedWorld: Clone + Eq + Hash {
+    type Agent: Clone + Ord + Hash;
+    type Cost: SearchCost;
+    type Context: ?Sized;
+
+    /// The moves `agents[idx]` can make, each as (that agent afterwards, the world afterwards,
+    /// cost of the step). The other agents are there to check for collisions.
+    fn agent_moves(
+        &self,
+        agents: &[Self::Agent],
+        idx: usize,
+        context: &Self::Context,
+    ) -> Vec<(Self::Agent, Self, Self::Cost)>;
+
+    fn is_goal(&self, agents: &[Self::Agent], context: &Self::Context) -> bool;
+
+    /// Like `SearchState::heuristic`, for the world and all the agents together.
+    fn heuristic(&self, _agents: &[Self::Agent], _context: &Self::Context) -> Self::Cost {
+        Self::Cost::zero()
+    }
+}
+
+/// N agents in a shared world as one search state. The agents are kept sorted, so swapping two
+/// of them gives the same state and symmetric orderings are only explored once. Every step's
+/// cost goes to the shared total, whichever agent made it.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Agents<W: SharedWorld, const N: usize> {
+    pub world: W,
+    agents: [W::Agent; N],
+}
+
+impl<W: SharedWorld, const N: usize> Agents<W, N> {
+    pub fn new(world: W, mut agents: [W::Agent; N]) -> Self {
+        agents.sort();
+        Self { world, agents }
+    }
+
+    /// In canonical (sorted) order.
+    pub fn agents(&self) -> &[W::Agent; N] {
+        &self.agents
+    }
+}
+
+impl<W: SharedWorld, const N: usize> SearchState for Agents<W, N> {
+    type Cost = W::Cost;
+    type Context = W::Context;
+
+    fn successors(&self, context: &Self::Context) -> Vec<(Self, Self::Cost)> {
+        let mut result = Vec::new();
+        for idx in 0..N {
+            // An identical agent just before this one would make exactly the same moves
+            if idx > 0 && self.agents[idx] == self.agents[idx - 1] {
+                continue;
+            }
+            for (agent, world, cost) in self.world.agent_moves(&self.agents, idx, context) {
+                let mut agents = self.agents.clone();
+                agents[idx] = agent;
+                result.push((Self::new(world, agents), cost));
+            }
+        }
+        result
+    }
+
+    fn is_goal(&self, context: &Self::Context) -> bool {
+        self.world.is_goal(&self.agents, context)
+    }
+
+    fn heuristic(&self, context: &Self::Context) -> Self::Cost {
+        self.world.heuristic(&self.agents, context)
+    }
+}
+
+/// Splits work between agents that each claim a subset of items, like valves to open, without
+/// searching all of them at once. `best_single` is the best a lone agent can do claiming exactly
+/// each subset; the result is the best total for `agents` of them with pairwise disjoint subsets.
+/// An agent may also claim nothing, for zero. Subsets are compressed to the items that appear in
+/// any of them, which must be at most 24, since this builds a table with an entry for every
+/// subset of those items (2^24 u32s is already 64MB).
+pub fn best_disjoint_split<V: Copy + Ord + Zero, const WORDS: usize>(
+    best_single: &HashMap<BitSet<WORDS>, V>,
+    agents: usize,
+) -> V {
+    let items = best_single
+        .keys()
+        .fold(BitSet::<WORDS>::new(), |acc, set| acc | *set)
+        .iter()
+        .collect::<Vec<_>>();
+    assert!(
+        items.len() <= 24,
+        "Too many items to split: {}",
+        items.len()
+    );
+    let compress = |set: &BitSet<WORDS>| {
+        items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| set.contains(**item))
+            .fold(0usize, |mask, (bit, _)| mask | 1 << bit)
+    };
+    let singles = best_single
+        .iter()
+        .map(|(set, value)| (compress(set), *value))
+        .collect::<Vec<_>>();
+
+    // within[mask] is the best a lone agent can do using only items in mask
+    let mut within = vec![V::zero(); 1 << items.len()];
+    for (mask, value) in &singles {
+        within[*mask] = within[*mask].max(*value);
+    }
+    for bit in 0..items.len() {
+        for mask in 0..within.len() {
+            if mask & 1 << bit != 0 {
+                within[mask] = within[mask].max(within[mask ^ 1 << bit]);
+            }
+        }
+    }
+
+    fn best<V: Copy + Ord + Zero>(
+        agents: usize,
+        mask: usize,
+        singles: &[(usize, V)],
+        within: &[V],
+        memo: &mut HashMap<(usize, usize), V>,
+    ) -> V {
+        if agents == 0 {
+            return V::zero();
+        }
+        if agents == 1 {
+            return within[mask];
+        }
+        if let Some(known) = memo.get(&(agents, mask)) {
+            return *known;
+        }
+        let mut result = best(agents - 1, mask, singles, within, memo);
+        for (claimed, value) in singles.iter().filter(|(claimed, _)| claimed & !mask == 0) {
+            let rest = best(agents - 1, mask & !claimed, singles, within, memo);
+            result = result.max(*value + rest);
+        }
+        memo.insert((agents, mask), result);
+        result
+    }
+
+    best(
+        agents,
+        within.len() - 1,
+        &singles,
+        &within,
+        &mut HashMap::new(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::bitset::BitSet64;
+    use crate::grid::{Grid, Neighbors};
+    use crate::point2::PointU;
+    use crate::search::{
+        best_disjoint_split, search, search_all_optimal, Agents, SearchState, SharedWorld,
+    };
+
+    #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+    struct Walker(PointU);
+
+    impl SearchState for Walker {
+        type Cost = u32;
+        type Context = Grid<u32>;
+
+        fn successors(&self, grid: &Grid<u32>) -> Vec<(Self, u32)> {
+            grid.neighbors_with_values(self.0, Neighbors::Four)
+                .map(|(p, risk)| (Walker(p), *risk))
+                .collect()
+        }
+
+        fn is_goal(&self, grid: &Grid<u32>) -> bool {
+            self.0 == PointU::new(grid.width() - 1, grid.height() - 1)
+        }
+
+        fn heuristic(&self, grid: &Grid<u32>) -> u32 {
+            (grid.width() - 1 - self.0.x + grid.height() - 1 - self.0.y) as u32
+        }
+    }
+
+    #[test]
+    fn test_chitons() {
+        // 2021 day 15 example
+        let input = [
+            "1163751742",
+            "1381373672",
+            "2136511328",
+            "3694931569",
+            "7463417111",
+            "1319128137",
+            "1359912421",
+            "3125421639",
+            "1293138521",
+            "2311944581",
+        ]
+        .join("\n");
+        let grid = Grid::<u32>::from_str(input, "\n", None, |c| c.parse().unwrap());
+        let result = search([Walker(PointU::ORIGIN)], &grid).unwrap();
+        assert_eq!(result.cost, 40);
+        assert_eq!(result.path.first(), Some(&Walker(PointU::ORIGIN)));
+        assert_eq!(result.path.len(), 19);
+        assert!(result.visited < grid.width() * grid.height());
+    }
+
+    #[test]
+    fn test_all_optimal() {
+        let grid = Grid::<u32>::from_str("111\n191\n111", "\n", None, |c| c.parse().unwrap());
+        let optimal = search_all_optimal([Walker(PointU::ORIGIN)], &grid).unwrap();
+        assert_eq!(optimal.cost, 4);
+        assert_eq!(optimal.paths().len(), 2);
+        assert_eq!(optimal.path().len(), 5);
+        let cells = optimal.cells(|w| w.0);
+        assert_eq!(cells.len(), 8);
+        assert!(!cells.contains(&PointU::new(1, 1)));
+        assert_eq!(optimal.goals().count(), 1);
+
+        let flat = Grid::<u32>::from_str("111\n111\n111", "\n", None, |c| c.parse().unwrap());
+        let optimal = search_all_optimal([Walker(PointU::ORIGIN)], &flat).unwrap();
+        assert_eq!(optimal.paths().len(), 6);
+        assert_eq!(optimal.states().len(), 9);
+    }
+
+    /// Targets on a track that haven't been stepped on yet.
+    #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+    struct Track(BitSet64);
+
+    impl SharedWorld for Track {
+        type Agent = usize;
+        type Cost = u32;
+        type Context = usize;
+
+        fn agent_moves(
+            &self,
+            agents: &[usize],
+            idx: usize,
+            len: &usize,
+        ) -> Vec<(usize, Self, u32)> {
+            let at = agents[idx];
+            [at.checked_sub(1), Some(at + 1).filter(|next| next < len)]
+                .into_iter()
+                .flatten()
+                .map(|next| (next, Track(self.0.without(next)), 1))
+                .collect()
+        }
+
+        fn is_goal(&self, _agents: &[usize], _len: &usize) -> bool {
+            self.0.is_empty()
+        }
+    }
+
+    #[test]
+    fn test_agents() {
+        let targets = Track([0, 9].into_iter().collect());
+        let start = Agents::new(targets.clone(), [5, 4]);
+        assert_eq!(start, Agents::new(targets, [4, 5]));
+        assert_eq!(start.agents(), &[4, 5]);
+        let result = search([start], &10).unwrap();
+        assert_eq!(result.cost, 8);
+        assert_eq!(result.path.last().unwrap().agents(), &[0, 9]);
+    }
+
+    #[test]
+    fn test_disjoint_split() {
+        let best_single: HashMap<BitSet64, u32> = [
+            (vec![], 0),
+            (vec![0], 5),
+            (vec![1], 4),
+            (vec![0, 1], 7),
+            (vec![2], 3),
+            (vec![0, 2], 6),
+        ]
+        .into_iter()
+        .map(|(items, value)| (items.into_iter().collect(), value))
+        .collect();
+        assert_eq!(best_disjoint_split(&best_single, 1), 7);
+        assert_eq!(best_disjoint_split(&best_single, 2), 10);
+        assert_eq!(best_disjoint_split(&best_single, 3), 12);
+        assert_eq!(best_disjoint_split(&best_single, 4), 12);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_disjoint_split_too_many_items() {
+        let best_single: HashMap<BitSet64, u32> = (0..25)
+            .map(|item| (BitSet64::new().with(item), 1))
+            .collect();
+        best_disjoint_split(&best_single, 2);
+    }
+}
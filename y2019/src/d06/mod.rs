@@ -1,25 +1,22 @@
-use itertools::Itertools;
 use std::collections::{HashSet, VecDeque};
+use util::graph::Graph;
 use util::split_once;
 
-const EMPTY_VEC: Vec<&str> = Vec::new();
-
 pub fn main() {
     // let input = include_str!("example_input.txt").trim();
     let input = include_str!("actual_input.txt").trim();
 
-    let graph = input.lines().map(|l| split_once(l, ")")).into_group_map();
-    let reverse_graph = graph
-        .iter()
-        .flat_map(|(k, v)| v.iter().map(move |val| (*val, *k)))
-        .into_group_map();
+    let mut graph: Graph<&str> = Graph::directed();
+    for (orbited, orbiter) in input.lines().map(|l| split_once(l, ")")) {
+        graph.add_edge(orbited, orbiter);
+    }
 
     let mut frontier = VecDeque::new();
-    frontier.push_back((0, "COM"));
+    frontier.push_back((0, graph.index_of("COM").unwrap()));
     let mut total = 0;
     while let Some((depth, node)) = frontier.pop_front() {
         total += depth;
-        for orbiter in graph.get(node).unwrap_or(&EMPTY_VEC).iter() {
+        for orbiter in graph.neighbors(node) {
             frontier.push_back((depth + 1, orbiter));
         }
     }
@@ -28,20 +25,15 @@ pub fn main() {
 
     let mut frontier = VecDeque::new();
     let mut seen = HashSet::new();
-    frontier.push_back((0, "YOU"));
+    frontier.push_back((0, graph.index_of("YOU").unwrap()));
     while let Some((depth, node)) = frontier.pop_front() {
         if !seen.insert(node) {
             continue;
         }
-        if node == "SAN" {
+        if *graph.name(node) == "SAN" {
             println!("{}", depth - 2);
         }
-        for orbiter in graph
-            .get(node)
-            .unwrap_or(&EMPTY_VEC)
-            .iter()
-            .chain(reverse_graph.get(node).unwrap_or(&EMPTY_VEC).iter())
-        {
+        for orbiter in graph.neighbors(node).chain(graph.predecessors(node)) {
             frontier.push_back((depth + 1, orbiter));
         }
     }
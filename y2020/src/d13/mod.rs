@@ -1,3 +1,4 @@
+use util::cycle_helpers::{chinese_remainder, Congruence};
 use util::p_u64;
 
 pub fn main() {
@@ -17,19 +18,16 @@ pub fn main() {
         .unwrap();
     println!("{} x {} = {}", bus_id, wait_time, bus_id * wait_time);
 
-    let id_index_pairs = buses_raw
-        .split(',')
-        .enumerate()
-        .filter(|(_, id)| *id != "x")
-        .map(|(i, id)| (p_u64(id), i as u64))
-        .collect::<Vec<_>>();
-
-    let mut start = 0;
-    let (mut step, _) = id_index_pairs[0];
-    for i in 1..id_index_pairs.len() {
-        start = find_valid_schedule_start(start, step, &id_index_pairs[0..=i]);
-        step *= id_index_pairs[i].0;
-    }
+    // Bus `id` at index `i` must leave i minutes after the start, so start ≡ -i (mod id)
+    let start = chinese_remainder(
+        buses_raw
+            .split(',')
+            .enumerate()
+            .filter(|(_, id)| *id != "x")
+            .map(|(i, id)| Congruence::new(-(i as i64), p_u64(id) as i64)),
+    )
+    .unwrap()
+    .residue;
     println!("{}", start);
 
     // let (largest_id, offset) = id_index_pairs.iter().max().unwrap();
@@ -62,23 +60,3 @@ pub fn main() {
     //     values[2] - values[1]
     // )
 }
-
-fn find_valid_schedule_start(start: u64, step: u64, id_index_pairs: &[(u64, u64)]) -> u64 {
-    let mut current = start;
-    while id_index_pairs
-        .iter()
-        .any(|(id, index)| (current + index) % id != 0)
-    {
-        current += step;
-        // if &(current % 100_000_000_000) < largest_id {
-        //     println!("{}", current);
-        // }
-        // if current > 1067000 {
-        //     println!("{}", current);
-        // }
-        // if current > 1069000 {
-        //     panic!("Stop: {}", current);
-        // }
-    }
-    current
-}
@@ -2,6 +2,7 @@ use std::ops::RangeInclusive;
 
 use regex::Regex;
 
+use util::flow::bipartite_matching;
 use util::p_u32;
 
 #[derive(Debug)]
@@ -81,42 +82,21 @@ fn pt2(fields: &Vec<Field>, your_ticket: &Ticket, nearby_tickets: &[Ticket]) {
         .filter(|t| t.is_valid(fields))
         .collect::<Vec<_>>();
 
-    let mut field_and_indices = fields
+    let potential_indices = fields
         .iter()
         .map(|field| {
-            let mut potential_indices: Vec<usize> = Vec::new();
-            for i in 0..fields.len() {
-                if valid_tickets.iter().all(|t| field.is_valid(t.values[i])) {
-                    potential_indices.push(i);
-                }
-            }
-            (field, potential_indices)
+            (0..fields.len())
+                .filter(|i| valid_tickets.iter().all(|t| field.is_valid(t.values[*i])))
+                .collect::<Vec<_>>()
         })
         .collect::<Vec<_>>();
 
     let mut ordered_fields: Vec<Option<&Field>> = vec![None; fields.len()];
-    while !field_and_indices.is_empty() {
-        field_and_indices = field_and_indices
-            .into_iter()
-            .filter_map(|(f, idxs)| {
-                assert!(!idxs.is_empty());
-                if idxs.len() == 1 {
-                    let idx = idxs[0];
-                    assert!(ordered_fields[idx].is_none());
-                    ordered_fields[idx] = Some(f);
-                    // Done with this entry, remove it
-                    None
-                } else {
-                    // Filter down the used indices
-                    Some((
-                        f,
-                        idxs.into_iter()
-                            .filter(|i| ordered_fields[*i].is_none())
-                            .collect::<Vec<_>>(),
-                    ))
-                }
-            })
-            .collect();
+    for (field, idx) in fields
+        .iter()
+        .zip(bipartite_matching(&potential_indices, fields.len()))
+    {
+        ordered_fields[idx.expect("Field couldn't be matched to a column")] = Some(field);
     }
 
     let departure_product: u64 = ordered_fields
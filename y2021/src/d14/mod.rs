@@ -1,49 +1,36 @@
 use std::collections::HashMap;
 
-use itertools::{Itertools, MinMaxResult};
+use itertools::Itertools;
+use util::counter::Counter;
 use util::iter_helpers::IteratorHelpers;
 
 fn do_insertions(
-    pair_counts: HashMap<(char, char), usize>,
+    pair_counts: Counter<(char, char)>,
     insertions: &HashMap<(char, char), char>,
-) -> HashMap<(char, char), usize> {
-    let mut new_pair_counts = HashMap::new();
-    for ((a, b), count) in pair_counts {
-        match insertions.get(&(a, b)).cloned() {
-            Some(middle) => {
-                *new_pair_counts.entry((a, middle)).or_default() += count;
-                *new_pair_counts.entry((middle, b)).or_default() += count;
-            }
-            None => {
-                *new_pair_counts.entry((a, b)).or_default() += count;
-            }
-        }
-    }
-    new_pair_counts
+) -> Counter<(char, char)> {
+    pair_counts.flat_map_keys(|&(a, b)| match insertions.get(&(a, b)).cloned() {
+        Some(middle) => vec![(a, middle), (middle, b)],
+        None => vec![(a, b)],
+    })
 }
 
 fn get_min_max_diff_after_cycles(
-    mut pair_counts: HashMap<(char, char), usize>,
+    mut pair_counts: Counter<(char, char)>,
     insertions: &HashMap<(char, char), char>,
     last_char: char,
     num_cycles: usize,
-) -> usize {
+) -> u64 {
     for _ in 0..num_cycles {
         pair_counts = do_insertions(pair_counts, insertions);
     }
 
-    let mut counts = pair_counts
-        .into_iter()
-        // Ignore the second value since it'll be represented by the first part of the other pairs.
-        // This holds true for every value in the string *except* the last one, hence the adjustment
-        // for the last char.
-        .map(|((a, _), count)| (a, count))
-        .into_sum_map();
-    *counts.entry(last_char).or_default() += 1;
-    match counts.values().minmax() {
-        MinMaxResult::MinMax(min, max) => max - min,
-        _ => panic!(),
-    }
+    // Ignore the second value since it'll be represented by the first part of the other pairs.
+    // This holds true for every value in the string *except* the last one, hence the adjustment
+    // for the last char.
+    let mut counts = pair_counts.map_keys(|&(a, _)| a);
+    counts.add(last_char, 1);
+    let (min, max) = counts.values().min_max().unwrap();
+    max - min
 }
 
 pub fn main() {
@@ -55,11 +42,11 @@ pub fn main() {
     let initial_polymer = raw_start.chars().collect_vec();
     let last_char = *initial_polymer.last().unwrap();
 
-    let initial_pair_counts = initial_polymer
+    let initial_pair_counts: Counter<(char, char)> = initial_polymer
         .iter()
         .zip(initial_polymer.iter().skip(1))
         .map(|(a, b)| (*a, *b))
-        .into_count_map();
+        .into_counter();
 
     let insertions = raw_insertions
         .split('\n')
@@ -1,12 +1,14 @@
 use std::cmp::max;
 
-#[derive(Debug)]
+use util::memo::Memo;
+
+#[derive(Debug, Clone)]
 struct Player {
     position: u32,
     score: u32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Board {
     player1_turn: bool,
     next_num: u32,
@@ -18,6 +20,9 @@ struct Board {
 const DETERMINISTIC_WINNING_SCORE: u32 = 1000;
 const DIRAC_WINNING_SCORE: u32 = 21;
 
+/// (player1_turn, player1 position, player1 score, player2 position, player2 score)
+type DiracKey = (bool, u32, u32, u32, u32);
+
 const DICE_ROLLS: [(u32, u64); 7] = [
     // All possible outcomes of rolling a 3-sided die 3 times, with counts of each.
     (3, 1),
@@ -76,34 +81,41 @@ impl Board {
         lower_score.map(|s| s * self.total_rolls)
     }
 
-    fn num_winning_possibilities(&mut self) -> (u64, u64) {
-        if self.player1.score >= DIRAC_WINNING_SCORE {
-            return (1, 0);
-        } else if self.player2.score >= DIRAC_WINNING_SCORE {
-            return (0, 1);
-        }
-
-        let mut p1_wins = 0;
-        let mut p2_wins = 0;
-
-        for (movement, count) in DICE_ROLLS.iter() {
-            let old_position = self.current_player().position;
-            self.current_player().position = (self.current_player().position + movement) % 10;
-            self.current_player().score += self.current_player().position + 1;
-            // This needs to be *just* before and after the recursive call since we use its value
-            // in the `current_player()` function
-            self.player1_turn = !self.player1_turn;
-
-            let (p1_inner_wins, p2_inner_wins) = self.num_winning_possibilities();
-            p1_wins += p1_inner_wins * count;
-            p2_wins += p2_inner_wins * count;
-
-            self.player1_turn = !self.player1_turn;
-            self.current_player().score -= self.current_player().position + 1;
-            self.current_player().position = old_position;
-        }
+    /// Everything that affects who wins from here. The deterministic die's state doesn't.
+    fn dirac_key(&self) -> DiracKey {
+        (
+            self.player1_turn,
+            self.player1.position,
+            self.player1.score,
+            self.player2.position,
+            self.player2.score,
+        )
+    }
 
-        (p1_wins, p2_wins)
+    fn num_winning_possibilities(self, memo: &mut Memo<Board, DiracKey, (u64, u64)>) -> (u64, u64) {
+        memo.get(self, |memo, board| {
+            if board.player1.score >= DIRAC_WINNING_SCORE {
+                return (1, 0);
+            } else if board.player2.score >= DIRAC_WINNING_SCORE {
+                return (0, 1);
+            }
+
+            let mut p1_wins = 0;
+            let mut p2_wins = 0;
+
+            for (movement, count) in DICE_ROLLS.iter() {
+                let mut next = board.clone();
+                next.current_player().position = (next.current_player().position + movement) % 10;
+                next.current_player().score += next.current_player().position + 1;
+                next.player1_turn = !next.player1_turn;
+
+                let (p1_inner_wins, p2_inner_wins) = next.num_winning_possibilities(memo);
+                p1_wins += p1_inner_wins * count;
+                p2_wins += p2_inner_wins * count;
+            }
+
+            (p1_wins, p2_wins)
+        })
     }
 }
 
@@ -120,7 +132,7 @@ pub fn main() {
 
     println!("Part 1: {}", board.get_final_score().unwrap());
 
-    let mut board = Board::new(p1_start - 1, p2_start - 1);
-    let (p1_wins, p2_wins) = board.num_winning_possibilities();
+    let board = Board::new(p1_start - 1, p2_start - 1);
+    let (p1_wins, p2_wins) = board.num_winning_possibilities(&mut Memo::new(Board::dirac_key));
     println!("Part 2: {}", max(p1_wins, p2_wins));
 }
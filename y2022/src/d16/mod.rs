@@ -1,13 +1,14 @@
 #![allow(clippy::needless_question_mark)]
-use derivative::Derivative;
 use itertools::Itertools;
-use priority_queue::PriorityQueue;
 use recap::Recap;
 use serde::Deserialize;
 
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::{Display, Formatter};
-use std::time::{Instant};
+use std::time::Instant;
+
+use util::bitset::BitSet64;
+use util::search::{search, Agents, SharedWorld};
 
 #[derive(Deserialize, Recap, Debug)]
 #[recap(
@@ -20,7 +21,7 @@ struct ValveRaw {
 }
 
 // We use a usize instead of a string since it's *way* more memory efficient and therefore faster.
-#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+#[derive(Hash, Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Debug)]
 struct Valve(usize);
 
 impl Display for Valve {
@@ -29,12 +30,6 @@ impl Display for Valve {
     }
 }
 
-impl Valve {
-    fn mask(&self) -> u32 {
-        1 << self.0
-    }
-}
-
 fn find_distance(neighbors: &[Vec<Valve>], start: Valve, end: Valve) -> u32 {
     // BFS
     let mut frontier = VecDeque::from([(start, 0)]);
@@ -58,7 +53,6 @@ fn find_distance(neighbors: &[Vec<Valve>], start: Valve, end: Valve) -> u32 {
 
 struct Valves {
     rates: Vec<u32>,
-    neighbors: Vec<Vec<Valve>>,
     starting_valve: Valve,
     pairwise_distances: HashMap<(Valve, Valve), u32>,
 }
@@ -114,143 +108,112 @@ impl Valves {
 
         Self {
             rates,
-            neighbors,
             starting_valve,
             pairwise_distances,
         }
     }
-
-    fn rate(&self, valve: Valve) -> u32 {
-        self.rates[valve.0]
-    }
-
-    fn neighbors(&self, valve: Valve) -> impl Iterator<Item = &Valve> {
-        self.neighbors[valve.0].iter()
-    }
 }
 
-#[derive(Clone, Derivative)]
-#[derivative(Eq, PartialEq, Hash)]
-struct State<const NUM_AGENTS: usize> {
-    // #[derivative(PartialEq = "ignore", Hash = "ignore")]
-    // actions: Vec<String>,
-    open_valves: u32,
-    current_valves: [Valve; NUM_AGENTS],
-    current_active_agent: usize,
-    released_flow: u32,
-    time_remaining: u32,
+/// The valves still worth opening. Each agent is (minute it's next free, where it is), so the
+/// sorted order puts whoever is free soonest first and those who have stopped last. The cost is
+/// the flow lost to valves being closed, so minimizing it releases the most.
+#[derive(Hash, Eq, PartialEq, Clone, Debug)]
+struct Volcano {
+    closed_valves: BitSet64,
+    total_time: u32,
 }
 
-impl<const NUM_AGENTS: usize> State<NUM_AGENTS> {
-    fn open_current_valve(&self, rate: u32) -> Self {
-        let mut new_state = self.clone();
-        let valve_open_duration = new_state.time_remaining - 1;
-        // new_state.actions.push(format!(
-        //     "{}: {} - Opened ({} * {} = {})",
-        //     new_state.time_remaining,
-        //     new_state.current_valves[new_state.current_active_agent],
-        //     rate,
-        //     valve_open_duration,
-        //     rate * valve_open_duration
-        // ));
-        new_state.open_valves |= new_state.current_valves[new_state.current_active_agent].mask();
-        new_state.released_flow += rate * valve_open_duration;
-        new_state.advance()
-    }
-
-    fn move_to_valve(&self, valve: Valve) -> Self {
-        let mut new_state = self.clone();
-        // new_state.actions.push(format!(
-        //     "{}: {} - Moved to {}",
-        //     new_state.time_remaining,
-        //     new_state.current_valves[new_state.current_active_agent],
-        //     valve,
-        // ));
-        new_state.current_valves[new_state.current_active_agent] = valve;
-        new_state.advance()
+impl Volcano {
+    fn is_stopped(&self, agent: &(u32, Valve)) -> bool {
+        agent.0 == self.total_time
     }
+}
 
-    fn advance(mut self) -> Self {
-        self.current_active_agent = (self.current_active_agent + 1) % NUM_AGENTS;
-        if self.current_active_agent == 0 {
-            self.time_remaining -= 1;
+impl SharedWorld for Volcano {
+    type Agent = (u32, Valve);
+    type Cost = u32;
+    type Context = Valves;
+
+    fn agent_moves(
+        &self,
+        agents: &[(u32, Valve)],
+        idx: usize,
+        valves: &Valves,
+    ) -> Vec<((u32, Valve), Self, u32)> {
+        // Letting the agent that's free soonest go first still allows every plan, and stops each
+        // one from being found in every interleaving
+        if idx != 0 || self.is_stopped(&agents[0]) {
+            return vec![];
         }
-        self
+        let (minute, current) = agents[0];
+        let mut moves = self
+            .closed_valves
+            .iter()
+            .filter_map(|index| {
+                let valve = Valve(index);
+                let opened_at = minute + valves.pairwise_distances[&(current, valve)] + 1;
+                (opened_at < self.total_time).then(|| {
+                    let world = Volcano {
+                        closed_valves: self.closed_valves.without(index),
+                        total_time: self.total_time,
+                    };
+                    ((opened_at, valve), world, valves.rates[index] * opened_at)
+                })
+            })
+            .collect_vec();
+        // Or stop, and once nobody's left the remaining valves stay closed the whole time
+        let last_active = agents[1..].iter().all(|agent| self.is_stopped(agent));
+        let stop_cost = if last_active {
+            self.closed_valves
+                .iter()
+                .map(|index| valves.rates[index] * self.total_time)
+                .sum()
+        } else {
+            0
+        };
+        moves.push(((self.total_time, current), self.clone(), stop_cost));
+        moves
     }
 
-    fn is_valve_open(&self, valve: Valve) -> bool {
-        self.open_valves & valve.mask() != 0
+    fn is_goal(&self, agents: &[(u32, Valve)], _valves: &Valves) -> bool {
+        agents.iter().all(|agent| self.is_stopped(agent))
     }
-}
 
-fn enqueue_state<const NUM_AGENTS: usize>(
-    frontier: &mut PriorityQueue<State<NUM_AGENTS>, u32>,
-    valves: &Valves,
-    state: State<NUM_AGENTS>,
-) {
-    let max_remaining_flow_upper_bound: u32 = valves
-        .rates
-        .iter()
-        .enumerate()
-        .map(|(index, rate)| (Valve(index), rate))
-        .filter(|(valve, rate)| **rate > 0 && !state.is_valve_open(*valve))
-        .map(|(valve, rate)| {
-            let closest_distance = state
-                .current_valves
-                .iter()
-                .map(|other| valves.pairwise_distances[&(*other, valve)])
-                .min()
-                .unwrap();
-            rate * state.time_remaining.saturating_sub(1 + closest_distance)
-        })
-        .sum();
-    let priority = state.released_flow + max_remaining_flow_upper_bound;
-    frontier.push(state, priority);
-}
-
-fn do_a_star<const NUM_AGENTS: usize>(valves: &Valves, total_time: u32) -> u32 {
-    // Ye olde A*
-    let mut visited = HashSet::new();
-    let mut frontier = PriorityQueue::new();
-    frontier.push(
-        State {
-            // actions: vec![],
-            open_valves: 0,
-            current_valves: [valves.starting_valve; NUM_AGENTS],
-            current_active_agent: 0,
-            released_flow: 0,
-            time_remaining: total_time,
-        },
-        0,
-    );
-
-    while let Some((state, _)) = frontier.pop() {
-        if visited.contains(&state) {
-            continue;
-        }
-        visited.insert(state.clone());
-
-        if state.time_remaining == 0 {
-            // for action in state.actions.iter() {
-            //     dbg!(action);
-            // }
-            return state.released_flow;
-        }
-
-        let current_valve = state.current_valves[state.current_active_agent];
-
-        // Don't turn on the current valve, just go to another one
-        for neighbor in valves.neighbors(current_valve) {
-            enqueue_state(&mut frontier, valves, state.move_to_valve(*neighbor));
-        }
-
-        let rate = valves.rate(current_valve);
-        if rate > 0 && state.time_remaining > 0 && !state.is_valve_open(current_valve) {
-            enqueue_state(&mut frontier, valves, state.open_current_valve(rate));
+    fn heuristic(&self, agents: &[(u32, Valve)], valves: &Valves) -> u32 {
+        if self.is_goal(agents, valves) {
+            return 0;
         }
+        // Each valve opens no sooner than the closest agent could get there
+        self.closed_valves
+            .iter()
+            .map(|index| {
+                let soonest = agents
+                    .iter()
+                    .filter(|agent| !self.is_stopped(agent))
+                    .map(|(minute, at)| {
+                        minute + valves.pairwise_distances[&(*at, Valve(index))] + 1
+                    })
+                    .min()
+                    .unwrap()
+                    .min(self.total_time);
+                valves.rates[index] * soonest
+            })
+            .sum()
     }
+}
 
-    panic!("Unable to find solution!");
+fn max_pressure<const NUM_AGENTS: usize>(valves: &Valves, total_time: u32) -> u32 {
+    let useful_valves = (0..valves.rates.len())
+        .filter(|index| valves.rates[*index] > 0)
+        .collect::<BitSet64>();
+    let world = Volcano {
+        closed_valves: useful_valves,
+        total_time,
+    };
+    let start = Agents::new(world, [(0, valves.starting_valve); NUM_AGENTS]);
+    let lost = search([start], valves).unwrap().cost;
+    let total_rate: u32 = valves.rates.iter().sum();
+    total_rate * total_time - lost
 }
 
 pub fn main() {
@@ -260,12 +223,12 @@ pub fn main() {
     let valves = Valves::from_str(&input);
 
     let start = Instant::now();
-    let max_pressure_pt1 = do_a_star::<1>(&valves, 30);
+    let max_pressure_pt1 = max_pressure::<1>(&valves, 30);
     let end = Instant::now();
     println!("Part 1: {} (took {:?})", max_pressure_pt1, end - start);
 
     let start = Instant::now();
-    let max_pressure_pt2 = do_a_star::<2>(&valves, 26);
+    let max_pressure_pt2 = max_pressure::<2>(&valves, 26);
     let end = Instant::now();
     println!("Part 2: {} (took {:?})", max_pressure_pt2, end - start);
 }
@@ -1,13 +1,12 @@
 #![allow(clippy::needless_question_mark)]
 use itertools::Itertools;
-use priority_queue::PriorityQueue;
 use recap::Recap;
 use serde::Deserialize;
 
-use std::collections::{HashSet};
 use std::panic;
 use std::time::Instant;
-use util::{p_u32};
+use util::branch_and_bound::{maximize, BranchState, Strategy};
+use util::p_u32;
 
 #[derive(Clone, Copy, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
 enum Resource {
@@ -116,18 +115,35 @@ impl State {
     }
 }
 
-fn enqueue(frontier: &mut PriorityQueue<State, u32>, state: State) {
-    // Max possible additional geodes is (current_geode_robots) + (current_geode_robots + 1) + ...
-    // + (current_geode_robots + time_remaining)
-    // We can do that quickly with the formula to sum an arithmetic sequence:
-    // https://study.com/learn/lesson/sum-of-arithmetic-sequence-formula-examples-what-is-arithmetic-sequence.html#section---SumOfAnArithmeticSequence
-    let n = state.time_remaining;
-    let a = state.robots_available[Resource::Geode.index()];
-    // d == 1
-    let max_additional_geodes = if n == 0 { 0 } else { (n * (2 * a + n - 1)) / 2 };
-
-    let priority = state.resources_available[Resource::Geode.index()] + max_additional_geodes;
-    frontier.push(state, priority);
+impl BranchState for State {
+    type Value = u32;
+    type Context = [Robot];
+
+    fn children(&self, robots: &[Robot]) -> Vec<Self> {
+        if self.time_remaining == 0 {
+            return vec![];
+        }
+        let mut children = vec![self.clone().advance_time()];
+        children.extend(robots.iter().filter_map(|robot| self.build_robot(robot)));
+        children
+    }
+
+    fn value(&self, _robots: &[Robot]) -> Option<u32> {
+        (self.time_remaining == 0).then_some(self.resources_available[Resource::Geode.index()])
+    }
+
+    fn upper_bound(&self, _robots: &[Robot]) -> u32 {
+        // Max possible additional geodes is (current_geode_robots) + (current_geode_robots + 1) + ...
+        // + (current_geode_robots + time_remaining)
+        // We can do that quickly with the formula to sum an arithmetic sequence:
+        // https://study.com/learn/lesson/sum-of-arithmetic-sequence-formula-examples-what-is-arithmetic-sequence.html#section---SumOfAnArithmeticSequence
+        let n = self.time_remaining;
+        let a = self.robots_available[Resource::Geode.index()];
+        // d == 1
+        let max_additional_geodes = if n == 0 { 0 } else { (n * (2 * a + n - 1)) / 2 };
+
+        self.resources_available[Resource::Geode.index()] + max_additional_geodes
+    }
 }
 
 fn get_max_geodes_per_blueprint(blueprints: &[Vec<Robot>], initial_time: u32) -> Vec<u32> {
@@ -135,30 +151,16 @@ fn get_max_geodes_per_blueprint(blueprints: &[Vec<Robot>], initial_time: u32) ->
         .iter()
         .map(|robots| {
             let start = Instant::now();
-            let mut frontier = PriorityQueue::new();
-            enqueue(&mut frontier, State::initial(initial_time));
-            let mut visited = HashSet::new();
-            while let Some((node, _)) = frontier.pop() {
-                if node.time_remaining == 0 {
-                    let quality_level = node.resources_available[Resource::Geode.index()];
-                    dbg!(Instant::now() - start);
-                    dbg!(quality_level);
-                    return quality_level;
-                }
-                if visited.contains(&node) {
-                    continue;
-                }
-
-                enqueue(&mut frontier, node.clone().advance_time());
-                for robot in robots {
-                    if let Some(next) = node.build_robot(robot) {
-                        enqueue(&mut frontier, next);
-                    }
-                }
-                // Do this at the end so we don't have to clone the node.
-                visited.insert(node);
-            }
-            panic!("Unable to find path to the end???")
+            let result = maximize(
+                State::initial(initial_time),
+                robots.as_slice(),
+                Strategy::BestFirst,
+                true,
+            );
+            let quality_level = result.value().expect("Unable to find path to the end???");
+            dbg!(Instant::now() - start);
+            dbg!(quality_level);
+            quality_level
         })
         .collect_vec()
 }
@@ -74,7 +74,8 @@ pub fn main() {
         .filter(|k| k.ends_with('A'))
         .map(|k| get_num_steps(k, &instructions, &graph))
         // Figure out the first time these intersect
-        .find_first_common_cycle();
+        .find_first_common_cycle()
+        .unwrap();
 
     println!("Part 2: {:?}", cycle_lengths);
 }
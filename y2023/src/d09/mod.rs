@@ -1,4 +1,5 @@
 use itertools::Itertools;
+use util::iter_helpers::IteratorHelpers;
 use util::p_i32;
 
 pub fn main() {
@@ -28,15 +29,7 @@ fn predict_next(sequence: &[i32]) -> i32 {
     if sequence.iter().all(|e| *e == 0) {
         0
     } else {
-        let derivative = sequence
-            .iter()
-            .fold((vec![], None), |(mut deriv, prev), next| {
-                if let Some(prev) = prev {
-                    deriv.push(next - prev);
-                }
-                (deriv, Some(*next))
-            })
-            .0;
+        let derivative = sequence.iter().pairwise_differences().collect_vec();
 
         let derivative_next = predict_next(&derivative);
         sequence.last().unwrap() + derivative_next
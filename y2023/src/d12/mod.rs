@@ -1,9 +1,9 @@
-use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
 
 use itertools::Itertools;
 use serde::Serialize;
 
+use util::memo::Memo;
 use util::p_usize;
 
 #[derive(Clone, Copy, Hash, Eq, PartialEq, Serialize)]
@@ -42,11 +42,12 @@ struct MemoKey {
     current_cell: Cell,
 }
 
+type CountMemo = Memo<MemoKey, MemoKey, u64>;
+
 #[derive(Clone)]
 struct SpringRow {
     cells: Vec<Cell>,
     counts: Vec<usize>,
-    memo: HashMap<MemoKey, u64>,
 }
 
 impl SpringRow {
@@ -67,12 +68,48 @@ impl SpringRow {
                 .cycle()
                 .take(self.counts.len() * 5)
                 .collect_vec(),
-            memo: HashMap::new(),
+        }
+    }
+
+    /// Applies one known cell to the bad run, returning the new (count_idx, current_bad_count),
+    /// or None if the cell makes the row invalid.
+    fn step(
+        &self,
+        cell: Cell,
+        count_idx: usize,
+        current_bad_count: Option<usize>,
+    ) -> Option<(usize, Option<usize>)> {
+        match cell {
+            Cell::Good => {
+                if current_bad_count.is_none() {
+                    return Some((count_idx, None));
+                }
+                if current_bad_count.as_ref() != self.counts.get(count_idx) {
+                    // We finished a bad run with the wrong count
+                    return None;
+                }
+                // We finished a bad run with the right len, advance to next count and reset
+                Some((count_idx + 1, None))
+            }
+            Cell::Bad => {
+                // Increment or initialize our bad run count
+                let current_bad_count = current_bad_count.unwrap_or(0) + 1;
+                let invalid = self
+                    .counts
+                    .get(count_idx)
+                    // True if our current run is too long
+                    .map(|next_count| current_bad_count > *next_count)
+                    // True if we're done with counts but ran into a bad
+                    .unwrap_or(true);
+                (!invalid).then_some((count_idx, Some(current_bad_count)))
+            }
+            Cell::Unknown => panic!("Can't step through an unknown cell"),
         }
     }
 
     fn count_valid(
-        &mut self,
+        &self,
+        memo: &mut CountMemo,
         mut cell_idx: usize,
         mut count_idx: usize,
         mut current_bad_count: Option<usize>,
@@ -82,62 +119,25 @@ impl SpringRow {
         // current_bad_len = None iff prev is good
 
         while let Some(cell) = self.cells.get(cell_idx) {
-            match cell {
-                Cell::Good => {
-                    if current_bad_count.is_some() {
-                        if current_bad_count.as_ref() != self.counts.get(count_idx) {
-                            // We finished a bad run with the wrong count
-                            return 0;
-                        }
-                        // We finished a bad run with the right len, advance to next count and reset
-                        count_idx += 1;
-                        current_bad_count = None;
-                    }
-                }
-                Cell::Bad => {
-                    // Increment or initialize our bad run count
-                    current_bad_count = Some(current_bad_count.unwrap_or(0) + 1);
-                    let invalid = self
-                        .counts
-                        .get(count_idx)
-                        // True if our current run is too long
-                        .map(|next_count| current_bad_count.unwrap() > *next_count)
-                        // True if we're done with counts but ran into a bad
-                        .unwrap_or(true);
-                    if invalid {
-                        return 0;
-                    }
-                }
-                Cell::Unknown => {
-                    // Recurse for each possibility, without advancing counts
-                    let mut memo_key = MemoKey {
-                        cell_idx,
-                        count_idx,
-                        current_bad_count,
-                        current_cell: Cell::Good,
-                    };
-                    self.cells[cell_idx] = Cell::Good;
-                    let good_count = if let Some(existing) = self.memo.get(&memo_key) {
-                        *existing
-                    } else {
-                        let new = self.count_valid(cell_idx, count_idx, current_bad_count);
-                        self.memo.insert(memo_key.clone(), new);
-                        new
-                    };
-                    memo_key.current_cell = Cell::Bad;
-                    self.cells[cell_idx] = Cell::Bad;
-                    let bad_count = if let Some(existing) = self.memo.get(&memo_key) {
-                        *existing
-                    } else {
-                        let new = self.count_valid(cell_idx, count_idx, current_bad_count);
-                        self.memo.insert(memo_key.clone(), new);
-                        new
-                    };
-                    self.cells[cell_idx] = Cell::Unknown;
-
-                    return good_count + bad_count;
-                }
+            if *cell == Cell::Unknown {
+                // Recurse for each possibility, without advancing counts
+                return [Cell::Good, Cell::Bad]
+                    .into_iter()
+                    .map(|current_cell| {
+                        let key = MemoKey {
+                            cell_idx,
+                            count_idx,
+                            current_bad_count,
+                            current_cell,
+                        };
+                        memo.get(key, |memo, key| self.count_valid_assuming(memo, key))
+                    })
+                    .sum();
             }
+            let Some(next) = self.step(*cell, count_idx, current_bad_count) else {
+                return 0;
+            };
+            (count_idx, current_bad_count) = next;
             cell_idx += 1;
         }
         let is_valid = (count_idx == self.counts.len() - 1 || count_idx == self.counts.len())
@@ -145,6 +145,16 @@ impl SpringRow {
         // We reached the end, return a `1` iff our final state is valid
         is_valid as u64
     }
+
+    /// Like `count_valid`, with the unknown cell at the key's index taken to be its current_cell.
+    fn count_valid_assuming(&self, memo: &mut CountMemo, key: MemoKey) -> u64 {
+        match self.step(key.current_cell, key.count_idx, key.current_bad_count) {
+            Some((count_idx, current_bad_count)) => {
+                self.count_valid(memo, key.cell_idx + 1, count_idx, current_bad_count)
+            }
+            None => 0,
+        }
+    }
 }
 
 pub fn main() {
@@ -157,24 +167,28 @@ pub fn main() {
             let (cells_raw, counts_raw) = l.split_once(' ').unwrap();
             let cells = cells_raw.chars().map(Cell::from_char).collect_vec();
             let counts = counts_raw.split(',').map(p_usize).collect_vec();
-            SpringRow {
-                cells,
-                counts,
-                memo: HashMap::new(),
-            }
+            SpringRow { cells, counts }
         })
         .collect_vec();
 
+    // Each row has its own cells, so results can't be shared between rows
+    let mut memo = Memo::by_args();
     let p1 = spring_rows
         .iter()
-        .map(|r| r.clone().count_valid(0, 0, None))
+        .map(|r| {
+            memo.clear();
+            r.count_valid(&mut memo, 0, 0, None)
+        })
         .sum::<u64>();
 
     println!("Part 1: {}", p1);
 
     let p2 = spring_rows
         .iter()
-        .map(|r| r.expanded().count_valid(0, 0, None))
+        .map(|r| {
+            memo.clear();
+            r.expanded().count_valid(&mut memo, 0, 0, None)
+        })
         .sum::<u64>();
     println!("Part 2: {}", p2);
 }
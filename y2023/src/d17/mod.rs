@@ -1,24 +1,19 @@
-use std::collections::HashSet;
-
-use priority_queue::PriorityQueue;
-
 use util::grid::Grid;
 use util::p_i32;
 use util::point2::{Delta, DeltaU, PointU, Rotation};
+use util::search::{search, SearchResult, SearchState};
 
 #[derive(Clone, Eq, PartialEq, Hash)]
-struct State {
+struct State<const ULTRA: bool> {
     pos: PointU,
     dir: DeltaU,
-    cost: i32,
     len: u8,
-    history: Vec<PointU>,
 }
 
-impl State {
-    fn next(&self, grid: &Grid<i32>, dir: DeltaU, ultra: bool) -> Option<Self> {
-        let min_len = if ultra { 4 } else { 1 };
-        let max_len = if ultra { 10 } else { 3 };
+impl<const ULTRA: bool> State<ULTRA> {
+    fn next(&self, grid: &Grid<i32>, dir: DeltaU) -> Option<(Self, i32)> {
+        let min_len = if ULTRA { 4 } else { 1 };
+        let max_len = if ULTRA { 10 } else { 3 };
         let (offset, len) = if dir == self.dir {
             (dir, self.len + 1)
         } else {
@@ -27,69 +22,43 @@ impl State {
         grid.point_in_grid(self.pos, &offset)
             .filter(|_| len < max_len)
             .map(|pos| {
-                let cost = self.cost + self.pos.step_to(&pos).map(|p| grid[p]).sum::<i32>();
-                let mut history = self.history.clone();
-                history.push(pos);
-                State {
-                    pos,
-                    dir,
-                    cost,
-                    len,
-                    history,
-                }
+                let cost = self.pos.step_to(&pos).map(|p| grid[p]).sum::<i32>();
+                (State { pos, dir, len }, cost)
             })
     }
 }
 
-fn enqueue(priority_queue: &mut PriorityQueue<State, i32>, state: State) {
-    let cost = -state.cost;
-    priority_queue.push(state, cost);
+impl<const ULTRA: bool> SearchState for State<ULTRA> {
+    type Cost = i32;
+    type Context = Grid<i32>;
+
+    fn successors(&self, grid: &Grid<i32>) -> Vec<(Self, i32)> {
+        [
+            self.dir.rotate_about_origin_deg(Rotation::Deg270),
+            self.dir,
+            self.dir.rotate_about_origin_deg(Rotation::Deg90),
+        ]
+        .into_iter()
+        .filter_map(|dir| self.next(grid, dir))
+        .collect()
+    }
+
+    fn is_goal(&self, grid: &Grid<i32>) -> bool {
+        self.pos == PointU::new(grid.width() - 1, grid.height() - 1)
+    }
 }
 
-fn do_search(grid: &Grid<i32>, ultra: bool) -> State {
-    let mut frontier = PriorityQueue::new();
-    let mut initial_state = State {
+fn do_search<const ULTRA: bool>(grid: &Grid<i32>) -> SearchResult<State<ULTRA>> {
+    let initial_state = |dir| State {
         pos: PointU::ORIGIN,
-        dir: DeltaU::RIGHT,
-        cost: 0,
+        dir,
         len: 0,
-        history: Vec::new(),
     };
-    enqueue(&mut frontier, initial_state.clone());
-    initial_state.dir = Delta::DOWN;
-    enqueue(&mut frontier, initial_state);
-
-    let mut seen = HashSet::new();
-    let dest = PointU::new(grid.width() - 1, grid.height() - 1);
-    loop {
-        let (state, _) = frontier.pop().unwrap();
-        if state.pos == dest {
-            break state;
-        }
-        let seen_key = (state.pos, state.dir, state.len);
-        if seen.contains(&seen_key) {
-            continue;
-        }
-        seen.insert(seen_key);
-        for next_state in [
-            state.next(
-                grid,
-                state.dir.rotate_about_origin_deg(Rotation::Deg270),
-                ultra,
-            ),
-            state.next(grid, state.dir, ultra),
-            state.next(
-                grid,
-                state.dir.rotate_about_origin_deg(Rotation::Deg90),
-                ultra,
-            ),
-        ]
-        .into_iter()
-        .flatten()
-        {
-            enqueue(&mut frontier, next_state);
-        }
-    }
+    search(
+        [initial_state(DeltaU::RIGHT), initial_state(Delta::DOWN)],
+        grid,
+    )
+    .unwrap()
 }
 
 pub fn main() {
@@ -98,21 +67,21 @@ pub fn main() {
 
     let grid = Grid::<i32>::from_str(input, "\n", None, p_i32);
 
-    let p1 = do_search(&grid, false);
+    let p1 = do_search::<false>(&grid);
     // WHY ARE WE 1 OFF????
     println!("Part 1: {}", p1.cost + 1);
     // let mut debug_grid = Grid::<usize>::empty(grid.width(), grid.height());
-    // for (i, p) in p1.history.iter().enumerate() {
-    //     debug_grid[*p] = i + 1;
+    // for (i, state) in p1.path.iter().enumerate() {
+    //     debug_grid[state.pos] = i + 1;
     // }
     // dbg!(debug_grid);
 
-    let p2 = do_search(&grid, true);
+    let p2 = do_search::<true>(&grid);
     println!("Part 2: {}", p2.cost + 1);
 
     // let mut debug_grid = Grid::<usize>::empty(grid.width(), grid.height());
-    // for (i, p) in p2.history.iter().enumerate() {
-    //     debug_grid[*p] = i + 1;
+    // for (i, state) in p2.path.iter().enumerate() {
+    //     debug_grid[state.pos] = i + 1;
     // }
     // dbg!(debug_grid);
 }
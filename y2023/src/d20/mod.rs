@@ -225,9 +225,10 @@ pub fn main() {
     }
     let p2 = rx_parent_inputs_high
         .values()
-        // I... don't know why this needs to be offset 0 instead of the offset of n-1 it seems to be at, but oh well.
-        .map(|idxs| (0, idxs[0] + 1))
-        .find_first_common_cycle();
+        // Presses are 1-indexed, so the first high is on press idxs[0] + 1, which is also the period.
+        .map(|idxs| (idxs[0] + 1, idxs[0] + 1))
+        .find_first_common_cycle()
+        .unwrap();
 
     /*
     tx -> [(65581957384105 - 3768)/ 7537, 11306, 15075, 18844] (5) -- [3769, 3769, 3769, 3769]